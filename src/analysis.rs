@@ -0,0 +1,253 @@
+use crate::{
+    bitset::BitSet,
+    propagation::{self, Adjacency, Neighbours},
+    rules::RuleSet,
+    tile::Tile,
+    topology::{Moore, Square, Topology},
+    wave::{Direction, DIAGONAL_DIRECTIONS},
+};
+
+use std::{collections::HashSet, fmt};
+
+use strum::IntoEnumIterator;
+
+/// Represents the size of the square grid used by [`RuleSet::analyze`] to look for a consistent tiling.
+pub const ANALYSIS_GRID_SIZE: usize = 3;
+
+/// Represents the outcome of the static analysis of a [`RuleSet`], for the
+/// directions and the cells of a [`Topology`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Analysis<T: Tile> {
+    /// The variants that can never appear, because they have
+    /// no compatible neighbour in at least one direction.
+    pub unplaceable: Vec<T>,
+
+    /// The pairs of opposite directions for which there are no rules at all.
+    pub empty_axes: Vec<(Direction, Direction)>,
+
+    /// The variants that have compatible neighbours in every direction, but
+    /// only through variants that can never appear themselves.
+    pub dead_ends: Vec<T>,
+
+    /// The rules whose inverse rule (with swapped tiles and opposite direction) is
+    /// missing, which never take effect, since each tile checks its own neighbours.
+    pub missing_inverses: Vec<(T, T, Direction)>,
+
+    /// A consistent tiling of the cells of the topology, with one tile for each cell, if any.
+    pub tiling: Option<Vec<T>>,
+}
+
+impl<T: Tile> Analysis<T> {
+    /// Analyzes the given rules, for a tile set with the given variants, laid out
+    /// following the given topology.
+    pub(crate) fn of<P: Topology>(rules: &RuleSet<T>, variants: &[T], topology: &P) -> Self {
+        let unplaceable = variants
+            .iter()
+            .copied()
+            .filter(|tile| !placeable(rules, variants, topology, *tile, &HashSet::new()))
+            .collect::<Vec<_>>();
+
+        let directions = topology.directions();
+
+        let empty_axes = directions
+            .iter()
+            .enumerate()
+            .map(|(i, direction)| (i, *direction, topology.opposite(*direction)))
+            .filter(|(i, _, opposite)| !directions[..*i].contains(opposite))
+            .filter(|(_, direction, opposite)| {
+                rules.in_direction(*direction).next().is_none()
+                    && rules.in_direction(*opposite).next().is_none()
+            })
+            .map(|(_, direction, opposite)| (direction, opposite))
+            .collect();
+
+        let mut excluded = unplaceable.iter().copied().collect::<HashSet<_>>();
+
+        // keep excluding variants until none of the remaining ones depends on an excluded one
+        while let Some(tile) = variants.iter().copied().find(|tile| {
+            !excluded.contains(tile) && !placeable(rules, variants, topology, *tile, &excluded)
+        }) {
+            let _ = excluded.insert(tile);
+        }
+
+        let dead_ends = variants
+            .iter()
+            .copied()
+            .filter(|tile| excluded.contains(tile) && !unplaceable.contains(tile))
+            .collect();
+
+        let mut missing_inverses = rules
+            .iter()
+            .filter(|(first, second, direction)| {
+                !rules.contains((*second, *first, topology.opposite(*direction)))
+            })
+            .collect::<Vec<_>>();
+
+        missing_inverses.sort_by_key(|(first, second, direction)| {
+            (
+                variants.iter().position(|variant| variant == first),
+                variants.iter().position(|variant| variant == second),
+                Direction::iter().position(|d| d == *direction),
+            )
+        });
+
+        Self {
+            unplaceable,
+            empty_axes,
+            dead_ends,
+            missing_inverses,
+            tiling: find_tiling(rules, variants, topology),
+        }
+    }
+
+    /// Analyzes the given rules, for a tile set with the given variants, laid out
+    /// in an [`ANALYSIS_GRID_SIZE`] by [`ANALYSIS_GRID_SIZE`] grid of square cells,
+    /// whose diagonal neighbours are only taken into account if any rule refers to them.
+    pub(crate) fn of_grid(rules: &RuleSet<T>, variants: &[T]) -> Self {
+        if is_diagonal(rules) {
+            Self::of(
+                rules,
+                variants,
+                &Moore::new(ANALYSIS_GRID_SIZE, ANALYSIS_GRID_SIZE),
+            )
+        } else {
+            Self::of(
+                rules,
+                variants,
+                &Square::new(ANALYSIS_GRID_SIZE, ANALYSIS_GRID_SIZE),
+            )
+        }
+    }
+
+    /// Returns `true` if and only if the analysis found no issues.
+    pub fn is_ok(&self) -> bool {
+        self.unplaceable.is_empty()
+            && self.empty_axes.is_empty()
+            && self.dead_ends.is_empty()
+            && self.missing_inverses.is_empty()
+            && self.tiling.is_some()
+    }
+}
+
+impl<T: Tile + fmt::Debug> fmt::Display for Analysis<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.unplaceable
+            .iter()
+            .try_for_each(|tile| writeln!(f, "{:?} can never appear.", tile))?;
+
+        self.empty_axes.iter().try_for_each(|(first, second)| {
+            writeln!(f, "There are no {:?} or {:?} rules.", first, second)
+        })?;
+
+        self.dead_ends.iter().try_for_each(|tile| {
+            writeln!(
+                f,
+                "{:?} is a dead end, its neighbours can never appear.",
+                tile
+            )
+        })?;
+
+        self.missing_inverses
+            .iter()
+            .try_for_each(|(first, second, direction)| {
+                writeln!(
+                    f,
+                    "The rule ({:?}, {:?}, {:?}) has no inverse.",
+                    first, second, direction
+                )
+            })?;
+
+        if self.tiling.is_none() {
+            writeln!(f, "There is no consistent tiling of the analyzed cells.")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns `true` if and only if `first_tile` and `second_tile` can be placed next to
+/// each other, with `second_tile` in the `direction` direction, as waves laid out following
+/// the given topology do: both the rule and its inverse must be in the set.
+fn allows<T: Tile, P: Topology>(
+    rules: &RuleSet<T>,
+    topology: &P,
+    first_tile: T,
+    second_tile: T,
+    direction: Direction,
+) -> bool {
+    rules.contains((first_tile, second_tile, direction))
+        && rules.contains((second_tile, first_tile, topology.opposite(direction)))
+}
+
+/// Returns `true` if and only if any of the given rules refers to diagonal neighbours.
+pub(crate) fn is_diagonal<T: Tile>(rules: &RuleSet<T>) -> bool {
+    DIAGONAL_DIRECTIONS
+        .iter()
+        .any(|direction| rules.in_direction(*direction).next().is_some())
+}
+
+/// Returns `true` if and only if the given tile has a compatible neighbour in
+/// every direction of the given topology, ignoring the `excluded` variants.
+fn placeable<T: Tile, P: Topology>(
+    rules: &RuleSet<T>,
+    variants: &[T],
+    topology: &P,
+    tile: T,
+    excluded: &HashSet<T>,
+) -> bool {
+    topology.directions().iter().all(|direction| {
+        variants.iter().any(|neighbour| {
+            !excluded.contains(neighbour) && allows(rules, topology, tile, *neighbour, *direction)
+        })
+    })
+}
+
+/// Returns a consistent tiling of the cells of the given topology with the given
+/// variants, with one tile for each cell, if any.
+pub(crate) fn find_tiling<T: Tile, P: Topology>(
+    rules: &RuleSet<T>,
+    variants: &[T],
+    topology: &P,
+) -> Option<Vec<T>> {
+    let adjacency = Adjacency::new(rules, variants, topology);
+    let neighbours = Neighbours::new(topology);
+
+    let cells = (0..topology.cells_total()).collect::<Vec<_>>();
+    let mut domains = vec![BitSet::full(variants.len()); cells.len()];
+
+    propagation::propagate(&mut domains, &adjacency, &neighbours, cells).ok()?;
+
+    let domains = search(domains, variants.len(), &adjacency, &neighbours)?;
+
+    domains
+        .iter()
+        .map(|domain| domain.single_value().map(|variant| variants[variant]))
+        .collect()
+}
+
+/// Collapses the cell with the fewest variants left to each of its variants in turn,
+/// propagating every choice and backtracking whenever a cell is left without variants,
+/// and returns the domains once every cell has collapsed, if possible.
+fn search(
+    domains: Vec<BitSet>,
+    variants_total: usize,
+    adjacency: &Adjacency,
+    neighbours: &Neighbours,
+) -> Option<Vec<BitSet>> {
+    let Some(cell) = (0..domains.len())
+        .filter(|cell| domains[*cell].len() > 1)
+        .min_by_key(|cell| domains[*cell].len())
+    else {
+        return Some(domains);
+    };
+
+    domains[cell].iter().find_map(|variant| {
+        let mut next = domains.clone();
+
+        next[cell] = BitSet::single(variants_total, variant);
+
+        propagation::propagate(&mut next, adjacency, neighbours, vec![cell]).ok()?;
+
+        search(next, variants_total, adjacency, neighbours)
+    })
+}
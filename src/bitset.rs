@@ -0,0 +1,120 @@
+//! Fixed-size sets of small integers, used to represent the variants
+//! a tile can still collapse to through their indices.
+
+/// Represents the number of bits of each word.
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// Represents a set of integers in `0..capacity`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct BitSet {
+    words: Vec<u64>,
+    capacity: usize,
+}
+
+impl BitSet {
+    /// Returns a new empty set, that can contain the integers in `0..capacity`.
+    pub(crate) fn empty(capacity: usize) -> Self {
+        Self {
+            words: vec![0; capacity.div_ceil(WORD_BITS)],
+            capacity,
+        }
+    }
+
+    /// Returns a new set, containing every integer in `0..capacity`.
+    pub(crate) fn full(capacity: usize) -> Self {
+        let mut set = Self {
+            words: vec![u64::MAX; capacity.div_ceil(WORD_BITS)],
+            capacity,
+        };
+
+        if capacity % WORD_BITS != 0 {
+            if let Some(last) = set.words.last_mut() {
+                *last = (1 << (capacity % WORD_BITS)) - 1;
+            }
+        }
+
+        set
+    }
+
+    /// Returns a new set, containing only the given integer.
+    pub(crate) fn single(capacity: usize, value: usize) -> Self {
+        let mut set = Self::empty(capacity);
+
+        set.insert(value);
+
+        set
+    }
+
+    /// Inserts the given integer into the set.
+    pub(crate) fn insert(&mut self, value: usize) {
+        self.words[value / WORD_BITS] |= 1 << (value % WORD_BITS);
+    }
+
+    /// Removes the given integer from the set.
+    pub(crate) fn remove(&mut self, value: usize) {
+        self.words[value / WORD_BITS] &= !(1 << (value % WORD_BITS));
+    }
+
+    /// Returns the number of integers in the set.
+    pub(crate) fn len(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// Returns `true` if and only if the set is empty.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.words.iter().all(|w| *w == 0)
+    }
+
+    /// Returns `true` if and only if the set contains every integer in `0..capacity`.
+    pub(crate) fn is_full(&self) -> bool {
+        self.len() == self.capacity
+    }
+
+    /// Returns the only integer of the set, if it contains exactly one.
+    pub(crate) fn single_value(&self) -> Option<usize> {
+        let mut values = self.iter();
+
+        let value = values.next()?;
+
+        values.next().is_none().then_some(value)
+    }
+
+    /// Returns the integers of the set, in increasing order.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, word)| {
+            let mut word = *word;
+
+            std::iter::from_fn(move || {
+                (word != 0).then(|| {
+                    let bit = word.trailing_zeros() as usize;
+
+                    word &= word - 1;
+
+                    i * WORD_BITS + bit
+                })
+            })
+        })
+    }
+
+    /// Adds every integer of the given set to the set.
+    pub(crate) fn union_with(&mut self, other: &Self) {
+        self.words
+            .iter_mut()
+            .zip(&other.words)
+            .for_each(|(w, o)| *w |= o);
+    }
+
+    /// Removes every integer that isn't in the given set from the set,
+    /// and returns `true` if and only if the set has changed.
+    pub(crate) fn intersect_with(&mut self, other: &Self) -> bool {
+        let mut changed = false;
+
+        self.words.iter_mut().zip(&other.words).for_each(|(w, o)| {
+            changed |= *w & !o != 0;
+
+            *w &= o;
+        });
+
+        changed
+    }
+}
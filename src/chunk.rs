@@ -0,0 +1,225 @@
+//! Unbounded worlds, generated one chunk at a time.
+
+use crate::{
+    rules::RuleSet,
+    tile::Tile,
+    topology::Rect,
+    wave::{self, Wave, WaveError},
+};
+
+use std::{collections::HashMap, sync::Arc};
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use strum::IntoEnumIterator;
+
+/// Represents the number of times a chunk is collapsed before giving up.
+const ATTEMPTS: usize = 16;
+
+/// Represents an unbounded world of square cells, split into chunks of the same size
+/// identified by their coordinates `(x, y)`, with `y` growing downwards, which are
+/// generated on demand, each consistent with the chunks next to it generated before.
+///
+/// The generated chunks are kept, and their borders are pinned around each new chunk
+/// before collapsing it; each chunk is collapsed with a ChaCha random number generator
+/// seeded from the seed of the world and its coordinates, whose output doesn't depend
+/// on the platform or on the version of `rand`.
+///
+/// # Examples
+///
+/// ```
+/// # use wfc::{chunk::ChunkedWave, rules::RuleSet, tile::{MazeTile, Tile}};
+/// let mut world = ChunkedWave::<MazeTile>::new(8, 8, 42, RuleSet::from_tiles()).unwrap();
+///
+/// let first = world.chunk((0, 0)).unwrap();
+/// let second = world.chunk((1, 0)).unwrap();
+///
+/// // the corridors continue across the chunks
+/// let side = |tile: MazeTile, i| tile.sockets().unwrap().chars().nth(i);
+///
+/// for y in 0..8 {
+///     assert_eq!(side(first.get((7, y)).unwrap(), 3), side(second.get((0, y)).unwrap(), 2));
+/// }
+///
+/// // generated chunks are kept as they are
+/// assert_eq!(world.chunk((0, 0)).unwrap(), first);
+///
+/// // the same world is generated from the same seed, in the same order
+/// let mut again = ChunkedWave::<MazeTile>::new(8, 8, 42, RuleSet::from_tiles()).unwrap();
+///
+/// assert_eq!(again.chunk((0, 0)).unwrap(), first);
+/// assert_eq!(again.chunk((1, 0)).unwrap(), second);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ChunkedWave<T: Tile> {
+    chunk_width: usize,
+    chunk_height: usize,
+    seed: u64,
+    variants: Vec<T>,
+    rules: Arc<RuleSet<T>>,
+    weights: HashMap<T, f64>,
+    chunks: HashMap<(i64, i64), Wave<T>>,
+}
+
+impl<T: Tile + IntoEnumIterator> ChunkedWave<T> {
+    /// Returns a new world of chunks with the given dimensions, following the given rules,
+    /// whose chunks are generated from the given seed.
+    pub fn new<R: Into<Arc<RuleSet<T>>>>(
+        chunk_width: usize,
+        chunk_height: usize,
+        seed: u64,
+        rules: R,
+    ) -> Result<Self, WaveError> {
+        Self::with_variants(chunk_width, chunk_height, seed, T::iter().collect(), rules)
+    }
+}
+
+impl<T: Tile> ChunkedWave<T> {
+    /// Returns a new world of chunks with the given dimensions, following the given rules,
+    /// whose tiles can collapse to the given (distinct) variants, and whose chunks
+    /// are generated from the given seed.
+    ///
+    /// Returns [`WaveError::InvalidVariants`] if there are no variants,
+    /// or if any of them is repeated.
+    pub fn with_variants<R: Into<Arc<RuleSet<T>>>>(
+        chunk_width: usize,
+        chunk_height: usize,
+        seed: u64,
+        variants: Vec<T>,
+        rules: R,
+    ) -> Result<Self, WaveError> {
+        if chunk_width == 0 || chunk_height == 0 {
+            return Err(WaveError::ZeroDimension);
+        }
+
+        wave::check_variants(&variants)?;
+
+        Ok(Self {
+            chunk_width,
+            chunk_height,
+            seed,
+            variants,
+            rules: rules.into(),
+            weights: HashMap::new(),
+            chunks: HashMap::new(),
+        })
+    }
+
+    /// Returns the width of the chunks.
+    pub fn chunk_width(&self) -> usize {
+        self.chunk_width
+    }
+
+    /// Returns the height of the chunks.
+    pub fn chunk_height(&self) -> usize {
+        self.chunk_height
+    }
+
+    /// Returns the seed of the world.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Sets the weight of the given tile variant, as [`Wave::set_weight`] does.
+    pub fn set_weight(&mut self, tile: T, weight: f64) {
+        let _ = self.weights.insert(tile, weight);
+    }
+
+    /// Returns `true` if and only if the chunk in the given coordinates has been generated.
+    pub fn is_generated(&self, coords: (i64, i64)) -> bool {
+        self.chunks.contains_key(&coords)
+    }
+
+    /// Generates the chunk in the given coordinates, consistently with the chunks next to
+    /// it that have been generated before, and returns it as a collapsed wave; a chunk
+    /// that has already been generated is returned as it is.
+    ///
+    /// Since each chunk depends on the chunks next to it generated before, the same seed
+    /// only generates the same chunks if they're generated in the same order.
+    ///
+    /// Returns [`WaveError::UncollapsibleWave`] if the borders of its neighbours contradict
+    /// each other, or [`WaveError::NotFullyCollapsed`] if the chunk can't be collapsed
+    /// consistently with them.
+    pub fn chunk(&mut self, (x, y): (i64, i64)) -> Result<Wave<T>, WaveError> {
+        if let Some(chunk) = self.chunks.get(&(x, y)) {
+            return Ok(chunk.clone());
+        }
+
+        let (width, height) = (self.chunk_width, self.chunk_height);
+
+        let mut rng = ChaCha8Rng::seed_from_u64(chunk_seed(self.seed, (x, y)));
+
+        // the chunk is collapsed with a margin of one cell,
+        // where the borders of the chunks next to it are pinned
+        let borders = (0..height + 2)
+            .flat_map(|my| (0..width + 2).map(move |mx| (mx, my)))
+            .filter(|(mx, my)| *mx == 0 || *my == 0 || *mx == width + 1 || *my == height + 1)
+            .filter_map(|(mx, my)| {
+                let (dx, dy) = (mx as i64 - 1, my as i64 - 1);
+                let (w, h) = (width as i64, height as i64);
+
+                let neighbour = self
+                    .chunks
+                    .get(&(x + dx.div_euclid(w), y + dy.div_euclid(h)))?;
+
+                let domain =
+                    neighbour.domain((dx.rem_euclid(w) as usize, dy.rem_euclid(h) as usize));
+
+                Some(((mx, my), domain))
+            })
+            .collect::<HashMap<_, _>>();
+
+        let mut outcome = Err(WaveError::NotFullyCollapsed);
+
+        for _ in 0..ATTEMPTS {
+            let mut wave = self.wave(width + 2, height + 2)?;
+
+            wave.restrict_regions(
+                |coords| borders.get(&coords),
+                |domain, tile| domain.map_or(true, |domain| domain.contains(&tile)),
+            )?;
+
+            outcome = wave.collapse(&mut rng).map(|_| wave);
+
+            if outcome.is_ok() {
+                break;
+            }
+        }
+
+        let chunk = outcome?.crop(Rect::new(1, 1, width, height));
+
+        let _ = self.chunks.insert((x, y), chunk.clone());
+
+        Ok(chunk)
+    }
+
+    /// Returns a new wave with the given dimensions, following the rules
+    /// and the weights of the world.
+    fn wave(&self, width: usize, height: usize) -> Result<Wave<T>, WaveError> {
+        let mut wave = Wave::with_variants(
+            width,
+            height,
+            self.variants.clone(),
+            Arc::clone(&self.rules),
+        )?;
+
+        self.weights
+            .iter()
+            .for_each(|(tile, weight)| wave.set_weight(*tile, *weight));
+
+        Ok(wave)
+    }
+}
+
+/// Returns the seed of the chunk in the given coordinates, mixing the seed of the world
+/// with them through the SplitMix64 finalizer, so that nearby chunks get unrelated seeds.
+fn chunk_seed(seed: u64, (x, y): (i64, i64)) -> u64 {
+    [x as u64, y as u64].into_iter().fold(seed, |hash, value| {
+        let mut z = (hash ^ value).wrapping_add(0x9E37_79B9_7F4A_7C15);
+
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+
+        z ^ (z >> 31)
+    })
+}
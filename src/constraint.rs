@@ -0,0 +1,1273 @@
+//! Global constraints, which restrict the whole wave beyond the adjacency of its tiles.
+
+use crate::{
+    bitset::BitSet,
+    tile::Tile,
+    topology::{Grid, Topology},
+    wave::{Direction, WaveError, DIRECTIONS_ORDER},
+};
+
+use std::{
+    collections::{HashSet, VecDeque},
+    fmt,
+    sync::Arc,
+};
+
+use strum::IntoEnumIterator;
+
+/// Represents a global constraint of a wave, which removes from the domains of its cells
+/// the variants that can't satisfy it, whenever the wave propagates its changes.
+///
+/// A constraint is only checked against the domains it's given: it must return an error
+/// whenever they can't satisfy it, which includes the case of a fully collapsed wave that
+/// doesn't satisfy it.
+pub trait Constraint<T: Tile, P: Topology>: fmt::Debug + Send + Sync {
+    /// Removes from the given domains the variants that can't satisfy the constraint,
+    /// and returns an error if the domains can't satisfy it at all.
+    fn propagate(&self, domains: &mut Domains<'_, T, P>) -> Result<(), WaveError>;
+
+    /// Returns an error if the constraint can't apply to the given topology, such as
+    /// [`WaveError::OutOfBounds`] for coordinates out of its bounds: it's checked once,
+    /// when the constraint is added to a wave, and any topology is fine by default.
+    fn check(&self, _topology: &P) -> Result<(), WaveError> {
+        Ok(())
+    }
+}
+
+/// Represents the variants each cell of a wave can still collapse to,
+/// as seen by its [`Constraint`]s.
+#[derive(Debug)]
+pub struct Domains<'a, T, P> {
+    domains: &'a mut [BitSet],
+    variants: &'a [T],
+    topology: &'a P,
+    changed: Vec<bool>,
+}
+
+impl<'a, T: Tile, P: Topology> Domains<'a, T, P> {
+    /// Returns the domains of the cells of the given topology.
+    pub(crate) fn new(domains: &'a mut [BitSet], variants: &'a [T], topology: &'a P) -> Self {
+        Self {
+            changed: vec![false; domains.len()],
+            domains,
+            variants,
+            topology,
+        }
+    }
+
+    /// Returns the cells whose domains have changed.
+    pub(crate) fn changed(&self) -> Vec<usize> {
+        (0..self.changed.len())
+            .filter(|cell| self.changed[*cell])
+            .collect()
+    }
+
+    /// Returns the topology of the wave.
+    pub fn topology(&self) -> &'a P {
+        self.topology
+    }
+
+    /// Returns the number of cells of the wave.
+    pub fn cells_total(&self) -> usize {
+        self.domains.len()
+    }
+
+    /// Returns the variants the given cell can still collapse to.
+    pub fn variants(&self, cell: usize) -> impl Iterator<Item = T> + '_ {
+        self.domains[cell]
+            .iter()
+            .map(|variant| self.variants[variant])
+    }
+
+    /// Returns `true` if and only if the given cell can still collapse to the given variant.
+    pub fn contains(&self, cell: usize, tile: T) -> bool {
+        self.variants(cell).any(|v| v == tile)
+    }
+
+    /// Returns the variant the given cell has collapsed to, if any.
+    pub fn get(&self, cell: usize) -> Option<T> {
+        self.domains[cell]
+            .single_value()
+            .map(|variant| self.variants[variant])
+    }
+
+    /// Removes from the domain of the given cell the variants for which `f` returns `false`,
+    /// and returns [`WaveError::UncollapsibleWave`] if no variant is left.
+    pub fn retain<F: FnMut(T) -> bool>(&mut self, cell: usize, mut f: F) -> Result<(), WaveError> {
+        let variants = self.variants;
+
+        self.retain_variants(cell, |variant| f(variants[variant]))
+    }
+
+    /// Removes from the domain of the given cell the variants for which `f` returns `false`,
+    /// where variants are identified by their indices.
+    pub(crate) fn retain_variants<F: FnMut(usize) -> bool>(
+        &mut self,
+        cell: usize,
+        mut f: F,
+    ) -> Result<(), WaveError> {
+        let removed = self.domains[cell]
+            .iter()
+            .filter(|variant| !f(*variant))
+            .collect::<Vec<_>>();
+
+        if !removed.is_empty() {
+            removed
+                .into_iter()
+                .for_each(|variant| self.domains[cell].remove(variant));
+
+            self.changed[cell] = true;
+        }
+
+        if self.domains[cell].is_empty() {
+            return Err(WaveError::UncollapsibleWave);
+        }
+
+        Ok(())
+    }
+}
+
+/// Represents the constraints of a wave, which are equal only if they are the same.
+pub(crate) struct Constraints<T, P>(Vec<Arc<dyn Constraint<T, P>>>);
+
+impl<T: Tile, P: Topology> Constraints<T, P> {
+    /// Adds the given constraint.
+    pub(crate) fn push(&mut self, constraint: Arc<dyn Constraint<T, P>>) {
+        self.0.push(constraint);
+    }
+
+    /// Propagates every constraint over the given domains.
+    pub(crate) fn propagate(&self, domains: &mut Domains<'_, T, P>) -> Result<(), WaveError> {
+        self.0.iter().try_for_each(|c| c.propagate(domains))
+    }
+}
+
+impl<T, P> Default for Constraints<T, P> {
+    fn default() -> Self {
+        Self(Vec::new())
+    }
+}
+
+impl<T, P> Clone for Constraints<T, P> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T, P> PartialEq for Constraints<T, P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.len() == other.0.len() && self.0.iter().zip(&other.0).all(|(a, b)| Arc::ptr_eq(a, b))
+    }
+}
+
+impl<T: Tile, P: Topology> fmt::Debug for Constraints<T, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(&self.0).finish()
+    }
+}
+
+/// Represents the constraint of connected corridors: the sides of the tiles that
+/// connect are given, two neighbouring cells are connected if and only if both their
+/// touching sides connect, and every cell with at least a side that connects must be
+/// reachable from every other one.
+///
+/// Cells that are cut off from the corridors are closed, and cells (or connections)
+/// that are the only way between corridors are opened; collapsing a wave whose corridors
+/// are disconnected anyway fails with [`WaveError::NotFullyCollapsed`], and can be retried.
+///
+/// # Examples
+///
+/// ```
+/// # use wfc::{constraint::Connected, rules::RuleSet, tile::MazeTile, topology::Topology, wave::Wave};
+/// # use rand::thread_rng;
+/// let connected = Connected::<MazeTile>::from_sockets('1');
+///
+/// let wave = loop {
+///     let mut wave = Wave::<MazeTile>::from_tiles(8, 8).unwrap();
+///
+///     wave.add_constraint(connected.clone()).unwrap();
+///
+///     if wave.collapse(&mut thread_rng()).is_ok() {
+///         break wave;
+///     }
+/// };
+///
+/// // every corridor is reached from the first one, following the connected sides
+/// let topology = wave.topology();
+/// let tile = |cell| wave.get(topology.coords(cell)).unwrap();
+///
+/// let corridors = (0..64).filter(|cell| tile(*cell) != MazeTile::Empty).collect::<Vec<_>>();
+///
+/// let mut reached = vec![corridors[0]];
+/// let mut stack = vec![corridors[0]];
+///
+/// while let Some(cell) = stack.pop() {
+///     for (direction, other) in topology.directions().iter().filter_map(|d| Some((*d, topology.neighbour(cell, *d)?))) {
+///         if connected.connects(tile(cell), direction)
+///             && connected.connects(tile(other), direction.opposite())
+///             && !reached.contains(&other)
+///         {
+///             reached.push(other);
+///             stack.push(other);
+///         }
+///     }
+/// }
+///
+/// assert_eq!(reached.len(), corridors.len());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Connected<T: Tile> {
+    sides: HashSet<(T, Direction)>,
+}
+
+impl<T: Tile> Connected<T> {
+    /// Returns a new connectivity constraint, where each `(tile, direction)`
+    /// indicates that the side of `tile` in the `direction` direction connects.
+    pub fn new<I: IntoIterator<Item = (T, Direction)>>(sides: I) -> Self {
+        Self {
+            sides: sides.into_iter().collect(),
+        }
+    }
+
+    /// Returns `true` if and only if the side of the given tile in the given direction connects.
+    pub fn connects(&self, tile: T, direction: Direction) -> bool {
+        self.sides.contains(&(tile, direction))
+    }
+}
+
+impl<T: Tile + IntoEnumIterator> Connected<T> {
+    /// Returns a new connectivity constraint, where the sides of the variants that connect
+    /// are the ones whose [`Tile::sockets`] label is the given one.
+    pub fn from_sockets(label: char) -> Self {
+        Self::new(T::iter().flat_map(|tile| {
+            DIRECTIONS_ORDER
+                .iter()
+                .zip(tile.sockets().unwrap_or_default().chars())
+                .filter(move |(_, l)| *l == label)
+                .map(move |(direction, _)| (tile, *direction))
+        }))
+    }
+}
+
+impl<T: Tile + fmt::Debug + Send + Sync, P: Topology> Constraint<T, P> for Connected<T> {
+    fn propagate(&self, domains: &mut Domains<'_, T, P>) -> Result<(), WaveError> {
+        let corridors = Corridors::new(domains, |tile, direction| self.connects(tile, direction));
+
+        let required = (0..domains.cells_total())
+            .filter(|cell| corridors.required[*cell])
+            .collect::<Vec<_>>();
+
+        corridors.connect(domains, &required, true)
+    }
+}
+
+/// Represents the constraint of a path between two cells, given which sides of
+/// the tiles connect, as in [`Connected`]: both cells are part of the path, and
+/// the cells (or connections) that are the only way between them are opened.
+///
+/// The endpoints can also be pinned to given variants, through [`PathConstraint::pin`].
+///
+/// # Examples
+///
+/// ```
+/// # use wfc::{constraint::{Connected, PathConstraint}, rules::RuleSet, tile::MazeTile, topology::Topology, wave::Wave};
+/// # use rand::thread_rng;
+/// let connected = Connected::<MazeTile>::from_sockets('1');
+///
+/// // from the entrance on the left edge to the exit on the right one
+/// let wave = loop {
+///     let mut wave = Wave::<MazeTile>::from_tiles(10, 10).unwrap();
+///
+///     let path = PathConstraint::new(connected.clone(), (0, 4), (9, 7))
+///         .pin(MazeTile::HorizontalLine, MazeTile::HorizontalLine);
+///
+///     wave.add_constraint(path).unwrap();
+///
+///     if wave.collapse(&mut thread_rng()).is_ok() {
+///         break wave;
+///     }
+/// };
+///
+/// let topology = wave.topology();
+/// let tile = |cell| wave.get(topology.coords(cell)).unwrap();
+///
+/// let mut reached = vec![topology.cell((0, 4)).unwrap()];
+/// let mut stack = reached.clone();
+///
+/// while let Some(cell) = stack.pop() {
+///     for direction in topology.directions() {
+///         match topology.neighbour(cell, *direction) {
+///             Some(other)
+///                 if connected.connects(tile(cell), *direction)
+///                     && connected.connects(tile(other), direction.opposite())
+///                     && !reached.contains(&other) =>
+///             {
+///                 reached.push(other);
+///                 stack.push(other);
+///             }
+///             _ => {}
+///         }
+///     }
+/// }
+///
+/// assert!(reached.contains(&topology.cell((9, 7)).unwrap()));
+/// assert_eq!(wave.get((0, 4)), Some(MazeTile::HorizontalLine));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathConstraint<T: Tile, C> {
+    connected: Connected<T>,
+    start: C,
+    end: C,
+    pinned: Option<(T, T)>,
+}
+
+impl<T: Tile, C> PathConstraint<T, C> {
+    /// Returns a new path constraint, between the cells in the given coordinates,
+    /// where the sides that connect are the ones of the given constraint.
+    ///
+    /// Adding it to a wave fails if any of the coordinates is out of its bounds.
+    pub fn new(connected: Connected<T>, start: C, end: C) -> Self {
+        Self {
+            connected,
+            start,
+            end,
+            pinned: None,
+        }
+    }
+
+    /// Pins the first cell of the path to `start_tile` and the last one to
+    /// `end_tile`, such as the entrance and the exit of a level.
+    pub fn pin(mut self, start_tile: T, end_tile: T) -> Self {
+        self.pinned = Some((start_tile, end_tile));
+
+        self
+    }
+}
+
+impl<T, P> Constraint<T, P> for PathConstraint<T, P::Coords>
+where
+    T: Tile + fmt::Debug + Send + Sync,
+    P: Topology,
+    P::Coords: Send + Sync,
+{
+    fn propagate(&self, domains: &mut Domains<'_, T, P>) -> Result<(), WaveError> {
+        let ends = [self.start, self.end].map(|coords| {
+            domains
+                .topology()
+                .cell(coords)
+                .expect("coordinates checked when added")
+        });
+
+        if let Some((start_tile, end_tile)) = self.pinned {
+            domains.retain(ends[0], |tile| tile == start_tile)?;
+            domains.retain(ends[1], |tile| tile == end_tile)?;
+        }
+
+        let corridors = Corridors::new(domains, |tile, direction| {
+            self.connected.connects(tile, direction)
+        });
+
+        for cell in ends {
+            corridors.retain(domains, cell, |mask| mask != 0)?;
+        }
+
+        corridors.connect(domains, &ends, false)
+    }
+
+    fn check(&self, topology: &P) -> Result<(), WaveError> {
+        [self.start, self.end]
+            .into_iter()
+            .try_for_each(|coords| topology.cell(coords).map(|_| ()))
+            .ok_or(WaveError::OutOfBounds)
+    }
+}
+
+/// Represents the constraint of the number of cells that collapse to a variant, which
+/// must be between a minimum and a maximum, either absolute or relative to the number
+/// of cells of the wave.
+///
+/// Once the maximum is reached, the variant is removed from the other cells, and once
+/// only the minimum number of cells can collapse to it, they do; collapsing a wave
+/// whose count is out of bounds anyway fails with [`WaveError::UnsatisfiedCount`].
+///
+/// Adding a constraint whose minimum is greater than its maximum, or than the number
+/// of cells of the wave, fails with [`WaveError::UnsatisfiedCount`] right away.
+///
+/// # Examples
+///
+/// ```
+/// # use wfc::{constraint::Count, rules::RuleSet, tile::MazeTile, wave::{Wave, WaveError}};
+/// # use rand::thread_rng;
+/// let mut wave = Wave::<MazeTile>::from_tiles(10, 10).unwrap();
+///
+/// assert!(matches!(
+///     wave.add_constraint(Count::between(MazeTile::Empty, 5, 4)),
+///     Err(WaveError::UnsatisfiedCount)
+/// ));
+///
+/// let wave = loop {
+///     let mut wave = Wave::<MazeTile>::from_tiles(10, 10).unwrap();
+///
+///     wave.add_constraint(Count::exactly(MazeTile::CenterCross, 1)).unwrap();
+///     wave.add_constraint(Count::at_most_ratio(MazeTile::HorizontalLine, 0.05)).unwrap();
+///     wave.add_constraint(Count::at_least(MazeTile::Empty, 20)).unwrap();
+///
+///     if wave.collapse(&mut thread_rng()).is_ok() {
+///         break wave;
+///     }
+/// };
+///
+/// let tiles = (0..10)
+///     .flat_map(|y| (0..10).map(move |x| (x, y)))
+///     .filter_map(|coords| wave.get(coords))
+///     .collect::<Vec<_>>();
+///
+/// let count = |tile| tiles.iter().filter(|t| **t == tile).count();
+///
+/// assert_eq!(count(MazeTile::CenterCross), 1);
+/// assert!(count(MazeTile::HorizontalLine) <= 5);
+/// assert!(count(MazeTile::Empty) >= 20);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Count<T: Tile> {
+    tile: T,
+    min: Amount,
+    max: Amount,
+}
+
+/// Represents a number of cells, either absolute or relative to the number of cells of a wave.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Amount {
+    Cells(usize),
+    Ratio(f64),
+}
+
+impl<T: Tile> Count<T> {
+    /// Returns a new count constraint, where between `min` and `max` cells collapse to `tile`.
+    pub fn between(tile: T, min: usize, max: usize) -> Self {
+        Self {
+            tile,
+            min: Amount::Cells(min),
+            max: Amount::Cells(max),
+        }
+    }
+
+    /// Returns a new count constraint, where exactly `count` cells collapse to `tile`.
+    pub fn exactly(tile: T, count: usize) -> Self {
+        Self::between(tile, count, count)
+    }
+
+    /// Returns a new count constraint, where at least `min` cells collapse to `tile`.
+    pub fn at_least(tile: T, min: usize) -> Self {
+        Self::between(tile, min, usize::MAX)
+    }
+
+    /// Returns a new count constraint, where at most `max` cells collapse to `tile`.
+    pub fn at_most(tile: T, max: usize) -> Self {
+        Self::between(tile, 0, max)
+    }
+
+    /// Returns a new count constraint, where at least the given ratio
+    /// of the cells (between `0.0` and `1.0`) collapse to `tile`.
+    pub fn at_least_ratio(tile: T, ratio: f64) -> Self {
+        Self {
+            tile,
+            min: Amount::Ratio(ratio),
+            max: Amount::Cells(usize::MAX),
+        }
+    }
+
+    /// Returns a new count constraint, where at most the given ratio
+    /// of the cells (between `0.0` and `1.0`) collapse to `tile`.
+    pub fn at_most_ratio(tile: T, ratio: f64) -> Self {
+        Self {
+            tile,
+            min: Amount::Cells(0),
+            max: Amount::Ratio(ratio),
+        }
+    }
+}
+
+impl<T: Tile> Count<T> {
+    /// Returns the minimum and the maximum number of cells, out of the given total.
+    fn bounds(&self, cells_total: usize) -> (usize, usize) {
+        let min = match self.min {
+            Amount::Cells(cells) => cells,
+            Amount::Ratio(ratio) => (ratio * cells_total as f64).ceil() as usize,
+        };
+
+        let max = match self.max {
+            Amount::Cells(cells) => cells,
+            Amount::Ratio(ratio) => (ratio * cells_total as f64).floor() as usize,
+        };
+
+        (min, max)
+    }
+}
+
+impl<T: Tile + fmt::Debug + Send + Sync, P: Topology> Constraint<T, P> for Count<T> {
+    fn propagate(&self, domains: &mut Domains<'_, T, P>) -> Result<(), WaveError> {
+        let cells_total = domains.cells_total();
+
+        let (min, max) = self.bounds(cells_total);
+
+        let possible = (0..cells_total)
+            .filter(|cell| domains.contains(*cell, self.tile))
+            .collect::<Vec<_>>();
+
+        let collapsed = possible
+            .iter()
+            .filter(|cell| domains.get(**cell) == Some(self.tile))
+            .count();
+
+        if collapsed > max || possible.len() < min {
+            return Err(WaveError::UnsatisfiedCount);
+        }
+
+        if collapsed == max {
+            for cell in &possible {
+                if domains.get(*cell).is_none() {
+                    domains.retain(*cell, |v| v != self.tile)?;
+                }
+            }
+        } else if possible.len() == min {
+            for cell in &possible {
+                domains.retain(*cell, |v| v == self.tile)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check(&self, topology: &P) -> Result<(), WaveError> {
+        let cells_total = topology.cells_total();
+
+        let (min, max) = self.bounds(cells_total);
+
+        if min > max || min > cells_total {
+            return Err(WaveError::UnsatisfiedCount);
+        }
+
+        Ok(())
+    }
+}
+
+/// Represents the constraint of tiles kept apart from each other, where no two cells
+/// closer than a distance can collapse to the same given variant.
+///
+/// Distances are measured in steps between neighbours, such as the Manhattan
+/// distance on [`Square`] grids.
+///
+/// # Examples
+///
+/// ```
+/// # use wfc::{constraint::{Apart, Count, Within}, rules::RuleSet, tile::Tile, wave::{Direction, Wave}};
+/// # use strum::IntoEnumIterator;
+/// # use strum_macros::EnumIter;
+/// # use rand::thread_rng;
+/// #[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, Hash, Tile)]
+/// pub enum Room {
+///     Floor,
+///     Exit,
+///     Spawn,
+/// }
+///
+/// // any room can be next to any other one
+/// let rules = Room::iter()
+///     .flat_map(|a| Room::iter().map(move |b| (a, b)))
+///     .fold(RuleSet::new(), |rules, (a, b)| {
+///         rules.allow_both_ways(a, b, Direction::Up).allow_both_ways(a, b, Direction::Left)
+///     });
+///
+/// let mut wave = Wave::new(20, 20, rules).unwrap();
+///
+/// // exits are spread out, and the only spawn is close to the center
+/// wave.add_constraint(Apart::new(Room::Exit, 10)).unwrap();
+/// wave.add_constraint(Count::exactly(Room::Spawn, 1)).unwrap();
+/// wave.add_constraint(Within::new(Room::Spawn, (10, 10), 5)).unwrap();
+///
+/// wave.collapse(&mut thread_rng()).unwrap();
+///
+/// let find = |room| {
+///     (0..20)
+///         .flat_map(|y| (0..20).map(move |x| (x, y)))
+///         .filter(|coords| wave.get(*coords) == Some(room))
+///         .collect::<Vec<_>>()
+/// };
+///
+/// let distance = |(x, y): (usize, usize), (a, b): (usize, usize)| x.abs_diff(a) + y.abs_diff(b);
+///
+/// for first in find(Room::Exit) {
+///     assert!(find(Room::Exit).iter().all(|second| first == *second || distance(first, *second) >= 10));
+/// }
+///
+/// assert!(distance(find(Room::Spawn)[0], (10, 10)) <= 5);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Apart<T: Tile> {
+    tile: T,
+    distance: usize,
+}
+
+impl<T: Tile> Apart<T> {
+    /// Returns a new constraint, where no two cells closer than
+    /// the given distance collapse to the given variant.
+    pub fn new(tile: T, distance: usize) -> Self {
+        Self { tile, distance }
+    }
+}
+
+impl<T: Tile + fmt::Debug + Send + Sync, P: Topology> Constraint<T, P> for Apart<T> {
+    fn propagate(&self, domains: &mut Domains<'_, T, P>) -> Result<(), WaveError> {
+        let collapsed = (0..domains.cells_total())
+            .filter(|cell| domains.get(*cell) == Some(self.tile))
+            .collect::<Vec<_>>();
+
+        for cell in collapsed {
+            let close = within(domains.topology(), cell, self.distance.saturating_sub(1));
+
+            for other in close.into_iter().filter(|other| *other != cell) {
+                domains.retain(other, |tile| tile != self.tile)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Represents the constraint of tiles kept close to a cell, where only the cells
+/// within a distance from it can collapse to the given variant.
+///
+/// Distances are measured in steps between neighbours, as for [`Apart`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Within<T: Tile, C> {
+    tile: T,
+    coords: C,
+    distance: usize,
+}
+
+impl<T: Tile, C> Within<T, C> {
+    /// Returns a new constraint, where only the cells within the given distance
+    /// from the cell in the given coordinates collapse to the given variant.
+    ///
+    /// Adding it to a wave fails if the coordinates are out of its bounds.
+    pub fn new(tile: T, coords: C, distance: usize) -> Self {
+        Self {
+            tile,
+            coords,
+            distance,
+        }
+    }
+}
+
+impl<T, P> Constraint<T, P> for Within<T, P::Coords>
+where
+    T: Tile + fmt::Debug + Send + Sync,
+    P: Topology,
+    P::Coords: Send + Sync,
+{
+    fn propagate(&self, domains: &mut Domains<'_, T, P>) -> Result<(), WaveError> {
+        let topology = domains.topology();
+
+        let center = topology
+            .cell(self.coords)
+            .expect("coordinates checked when added");
+
+        let mut close = vec![false; domains.cells_total()];
+
+        within(topology, center, self.distance)
+            .into_iter()
+            .for_each(|cell| close[cell] = true);
+
+        for cell in (0..domains.cells_total()).filter(|cell| !close[*cell]) {
+            domains.retain(cell, |tile| tile != self.tile)?;
+        }
+
+        Ok(())
+    }
+
+    fn check(&self, topology: &P) -> Result<(), WaveError> {
+        topology
+            .cell(self.coords)
+            .map(|_| ())
+            .ok_or(WaveError::OutOfBounds)
+    }
+}
+
+/// Represents the constraint of a pattern of tiles, given as rows from the upper one,
+/// where `None` matches any tile: a forbidden pattern can't appear anywhere in the wave,
+/// while a required pattern must appear around each tile equal to its anchor tile.
+///
+/// The cells of a pattern are found by moving [`Direction::Down`] and then
+/// [`Direction::Right`] from its upper left cell (or [`Direction::Up`] and
+/// [`Direction::Left`], from its anchor), so adding a pattern to a wave whose
+/// topology lacks any of the directions it needs, such as a [`Hex`](crate::topology::Hex)
+/// map, returns [`WaveError::UnsupportedTopology`].
+///
+/// # Examples
+///
+/// ```
+/// # use wfc::{constraint::Pattern, rules::RuleSet, tile::MazeTile, wave::{Wave, WaveError}};
+/// # use rand::thread_rng;
+/// let mut wave = Wave::<MazeTile>::from_tiles(10, 10).unwrap();
+///
+/// wave.set_weight(MazeTile::CenterCross, 20.0);
+///
+/// // no 2x2 blocks of crosses
+/// let cross = Some(MazeTile::CenterCross);
+///
+/// wave.add_constraint(Pattern::forbidden([vec![cross, cross], vec![cross, cross]])).unwrap();
+///
+/// // every `┳` is part of a T-junction with three lines
+/// let junction = [
+///     vec![Some(MazeTile::HorizontalLine), Some(MazeTile::TShaped), Some(MazeTile::HorizontalLine)],
+///     vec![None, Some(MazeTile::VerticalLine), None],
+/// ];
+///
+/// wave.add_constraint(Pattern::required(junction.clone(), (1, 0))).unwrap();
+///
+/// // hexagonal maps have no square directions to follow
+/// let mut hex = Wave::<MazeTile, _>::hex(10, 10, RuleSet::from_tiles()).unwrap();
+///
+/// assert!(matches!(
+///     hex.add_constraint(Pattern::required(junction, (1, 0))),
+///     Err(WaveError::UnsupportedTopology)
+/// ));
+///
+/// wave.collapse(&mut thread_rng()).unwrap();
+///
+/// for (x, y) in (0..9).flat_map(|y| (0..9).map(move |x| (x, y))) {
+///     let block = [(x, y), (x + 1, y), (x, y + 1), (x + 1, y + 1)];
+///
+///     assert!(block.iter().any(|coords| wave.get(*coords) != cross));
+///
+///     if wave.get((x, y)) == Some(MazeTile::TShaped) {
+///         assert_eq!(wave.get((x, y + 1)), Some(MazeTile::VerticalLine));
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern<T: Tile> {
+    rows: Vec<Vec<Option<T>>>,
+    anchor: Option<(usize, usize)>,
+}
+
+impl<T: Tile> Pattern<T> {
+    /// Returns a new constraint, where the given pattern can't appear anywhere.
+    pub fn forbidden<I: IntoIterator<Item = Vec<Option<T>>>>(rows: I) -> Self {
+        Self {
+            rows: rows.into_iter().collect(),
+            anchor: None,
+        }
+    }
+
+    /// Returns a new constraint, where the given pattern must appear around each tile
+    /// equal to the one of the pattern in the given coordinates `(x, y)`, its anchor.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the anchor is out of the pattern, or if it matches any tile.
+    pub fn required<I: IntoIterator<Item = Vec<Option<T>>>>(
+        rows: I,
+        anchor: (usize, usize),
+    ) -> Self {
+        let rows = rows.into_iter().collect::<Vec<_>>();
+
+        assert!(
+            rows.get(anchor.1)
+                .and_then(|row| row.get(anchor.0))
+                .is_some_and(Option::is_some),
+            "anchor must be a tile of the pattern"
+        );
+
+        Self {
+            rows,
+            anchor: Some(anchor),
+        }
+    }
+
+    /// Returns the cells of the pattern, together with their tiles, placed so that its
+    /// cell in the given coordinates is the given cell, if the pattern fits there.
+    fn place<P: Topology>(
+        &self,
+        topology: &P,
+        cell: usize,
+        (x, y): (usize, usize),
+    ) -> Option<Vec<(usize, T)>> {
+        self.rows
+            .iter()
+            .enumerate()
+            .flat_map(|(py, row)| {
+                row.iter()
+                    .enumerate()
+                    .filter_map(move |(px, tile)| Some((px, py, (*tile)?)))
+            })
+            .map(|(px, py, tile)| {
+                let dx = px as isize - x as isize;
+                let dy = py as isize - y as isize;
+
+                Some((walk(topology, cell, dx, dy)?, tile))
+            })
+            .collect()
+    }
+
+    /// Returns the directions followed to find the cells of the pattern from its anchor,
+    /// or from its upper left cell.
+    fn directions(&self) -> Vec<Direction> {
+        let (x, y) = self.anchor.unwrap_or((0, 0));
+
+        let tiles = self.rows.iter().enumerate().flat_map(|(py, row)| {
+            row.iter()
+                .enumerate()
+                .filter(|(_, tile)| tile.is_some())
+                .map(move |(px, _)| (px, py))
+        });
+
+        let mut directions = Vec::new();
+
+        for (px, py) in tiles {
+            let steps = [
+                (py < y, Direction::Up),
+                (py > y, Direction::Down),
+                (px < x, Direction::Left),
+                (px > x, Direction::Right),
+            ];
+
+            for (_, direction) in steps.into_iter().filter(|(needed, _)| *needed) {
+                if !directions.contains(&direction) {
+                    directions.push(direction);
+                }
+            }
+        }
+
+        directions
+    }
+}
+
+impl<T: Tile + fmt::Debug + Send + Sync, P: Topology> Constraint<T, P> for Pattern<T> {
+    fn propagate(&self, domains: &mut Domains<'_, T, P>) -> Result<(), WaveError> {
+        let topology = domains.topology();
+
+        for cell in 0..domains.cells_total() {
+            match self.anchor {
+                None => {
+                    let Some(placed) = self.place(topology, cell, (0, 0)) else {
+                        continue;
+                    };
+
+                    if placed.iter().any(|(c, tile)| !domains.contains(*c, *tile)) {
+                        continue;
+                    }
+
+                    let pending = placed
+                        .iter()
+                        .filter(|(c, tile)| domains.get(*c) != Some(*tile))
+                        .collect::<Vec<_>>();
+
+                    match pending[..] {
+                        [] => return Err(WaveError::UncollapsibleWave),
+                        [(c, tile)] => domains.retain(*c, |v| v != *tile)?,
+                        _ => {}
+                    }
+                }
+                Some((x, y)) => {
+                    let anchor = self.rows[y][x].unwrap();
+
+                    if !domains.contains(cell, anchor) {
+                        continue;
+                    }
+
+                    let placed = self
+                        .place(topology, cell, (x, y))
+                        .filter(|p| p.iter().all(|(c, tile)| domains.contains(*c, *tile)));
+
+                    match placed {
+                        None => domains.retain(cell, |v| v != anchor)?,
+                        Some(placed) if domains.get(cell) == Some(anchor) => {
+                            for (c, tile) in placed {
+                                domains.retain(c, |v| v == tile)?;
+                            }
+                        }
+                        Some(_) => {}
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check(&self, topology: &P) -> Result<(), WaveError> {
+        if self
+            .directions()
+            .iter()
+            .all(|direction| topology.directions().contains(direction))
+        {
+            Ok(())
+        } else {
+            Err(WaveError::UnsupportedTopology)
+        }
+    }
+}
+
+/// Represents the constraint of a symmetric grid, where the cells mirrored to each
+/// other collapse to tiles mirrored to each other, following [`Tile::symmetry`].
+///
+/// It applies to any [`Grid`] of square cells, where the cells whose mirrored cell is
+/// a hole of the grid are left unconstrained; adding it to a staggered grid, such as a
+/// [`Hex`](crate::topology::Hex) map, whose rows can't be mirrored, returns
+/// [`WaveError::UnsupportedTopology`].
+///
+/// # Examples
+///
+/// ```
+/// # use wfc::{constraint::Symmetric, rules::RuleSet, tile::{MazeTile, Tile}, wave::{Wave, WaveError}};
+/// # use rand::thread_rng;
+/// // staggered rows can't be mirrored
+/// let mut hex = Wave::<MazeTile, _>::hex(8, 8, RuleSet::from_tiles()).unwrap();
+///
+/// assert!(matches!(hex.add_constraint(Symmetric::Both), Err(WaveError::UnsupportedTopology)));
+///
+/// let mut wave = Wave::<MazeTile>::from_tiles(8, 8).unwrap();
+///
+/// wave.add_constraint(Symmetric::Both).unwrap();
+///
+/// wave.collapse(&mut thread_rng()).unwrap();
+///
+/// for (x, y) in (0..8).flat_map(|y| (0..8).map(move |x| (x, y))) {
+///     let tile = wave.get((x, y)).unwrap();
+///
+///     assert_eq!(wave.get((7 - x, y)), Some(tile.symmetry().horizontal));
+///     assert_eq!(wave.get((x, 7 - y)), Some(tile.symmetry().vertical));
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Symmetric {
+    /// Mirrored across the vertical axis, swapping the left and right sides.
+    Horizontal,
+
+    /// Mirrored across the horizontal axis, swapping the upper and lower sides.
+    Vertical,
+
+    /// Mirrored across both axes.
+    Both,
+
+    /// Rotated by 180 degrees, which mirrors across both axes at once.
+    Rotational,
+}
+
+impl<T: Tile + fmt::Debug + Send + Sync, P: Grid> Constraint<T, P> for Symmetric {
+    fn propagate(&self, domains: &mut Domains<'_, T, P>) -> Result<(), WaveError> {
+        let topology = domains.topology();
+
+        // whether each mirroring flips the horizontal and the vertical axis
+        let mirrors: &[(bool, bool)] = match self {
+            Self::Horizontal => &[(true, false)],
+            Self::Vertical => &[(false, true)],
+            Self::Both => &[(true, false), (false, true)],
+            Self::Rotational => &[(true, true)],
+        };
+
+        for (horizontal, vertical) in mirrors {
+            let mirror = |tile: T| {
+                let tile = if *horizontal {
+                    tile.symmetry().horizontal
+                } else {
+                    tile
+                };
+
+                if *vertical {
+                    tile.symmetry().vertical
+                } else {
+                    tile
+                }
+            };
+
+            for cell in 0..domains.cells_total() {
+                let (x, y) = topology.coords(cell);
+
+                let x = if *horizontal {
+                    topology.width() - 1 - x
+                } else {
+                    x
+                };
+                let y = if *vertical {
+                    topology.height() - 1 - y
+                } else {
+                    y
+                };
+
+                let Some(other) = topology.cell((x, y)) else {
+                    continue;
+                };
+
+                if other == cell {
+                    domains.retain(cell, |tile| mirror(tile) == tile)?;
+                } else {
+                    let mirrored = domains.variants(other).map(mirror).collect::<Vec<_>>();
+
+                    domains.retain(cell, |tile| mirrored.contains(&tile))?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check(&self, topology: &P) -> Result<(), WaveError> {
+        if topology.is_staggered() {
+            return Err(WaveError::UnsupportedTopology);
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the cells within the given distance from the given
+/// cell, in steps between neighbours, nearest first.
+fn within<P: Topology>(topology: &P, cell: usize, max: usize) -> Vec<usize> {
+    let mut visited = vec![false; topology.cells_total()];
+    let mut found = Vec::new();
+    let mut queue = VecDeque::from([(cell, 0)]);
+
+    visited[cell] = true;
+
+    while let Some((cell, distance)) = queue.pop_front() {
+        found.push(cell);
+
+        if distance == max {
+            continue;
+        }
+
+        for direction in topology.directions() {
+            match topology.neighbour(cell, *direction) {
+                Some(other) if !visited[other] => {
+                    visited[other] = true;
+                    queue.push_back((other, distance + 1));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    found
+}
+
+/// Returns the cell found by moving `dy` cells [`Direction::Down`] (or [`Direction::Up`],
+/// if negative) from the given cell, and then `dx` cells [`Direction::Right`] (or
+/// [`Direction::Left`], if negative), if any.
+fn walk<P: Topology>(topology: &P, cell: usize, dx: isize, dy: isize) -> Option<usize> {
+    let vertical = if dy < 0 {
+        Direction::Up
+    } else {
+        Direction::Down
+    };
+    let horizontal = if dx < 0 {
+        Direction::Left
+    } else {
+        Direction::Right
+    };
+
+    let cell = (0..dy.unsigned_abs()).try_fold(cell, |c, _| topology.neighbour(c, vertical))?;
+
+    (0..dx.unsigned_abs()).try_fold(cell, |c, _| topology.neighbour(c, horizontal))
+}
+
+/// Represents the corridors a wave can still collapse to, where each cell
+/// is identified by the directions (by index) its variants can connect to.
+struct Corridors {
+    directions: Vec<Direction>,
+    opposites: Vec<Option<usize>>,
+    masks: Vec<u32>,
+    open: Vec<u32>,
+    required: Vec<bool>,
+}
+
+impl Corridors {
+    /// Returns the corridors of the given domains, given which sides of the tiles connect.
+    fn new<T: Tile, P: Topology, F: Fn(T, Direction) -> bool>(
+        domains: &Domains<'_, T, P>,
+        connects: F,
+    ) -> Self {
+        let directions = domains.topology.directions().to_vec();
+
+        // the directions a variant connects to are stored as the bits of a mask
+        assert!(
+            directions.len() <= u32::BITS as usize,
+            "too many directions for a mask"
+        );
+
+        let opposites = directions
+            .iter()
+            .map(|d| {
+                directions
+                    .iter()
+                    .position(|o| *o == domains.topology.opposite(*d))
+            })
+            .collect();
+
+        let masks = domains
+            .variants
+            .iter()
+            .map(|tile| {
+                directions
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, d)| connects(*tile, **d))
+                    .fold(0, |mask, (i, _)| mask | 1 << i)
+            })
+            .collect::<Vec<u32>>();
+
+        Self {
+            open: domains
+                .domains
+                .iter()
+                .map(|domain| domain.iter().fold(0, |open, v| open | masks[v]))
+                .collect(),
+            required: domains
+                .domains
+                .iter()
+                .map(|domain| !domain.is_empty() && domain.iter().all(|v| masks[v] != 0))
+                .collect(),
+            directions,
+            opposites,
+            masks,
+        }
+    }
+
+    /// Returns the cell connected to the given cell in the direction with the given index,
+    /// if the cells can still be connected to each other.
+    fn edge<T: Tile, P: Topology>(
+        &self,
+        domains: &Domains<'_, T, P>,
+        cell: usize,
+        direction: usize,
+    ) -> Option<usize> {
+        let other = domains
+            .topology
+            .neighbour(cell, self.directions[direction])?;
+        let opposite = self.opposites[direction]?;
+
+        (self.open[cell] & 1 << direction != 0 && self.open[other] & 1 << opposite != 0)
+            .then_some(other)
+    }
+
+    /// Keeps the given cells connected to each other, opening the cells and the connections
+    /// that are the only way between them, and closing the cells that are cut off from
+    /// them if `close_others` is `true`.
+    fn connect<T: Tile, P: Topology>(
+        &self,
+        domains: &mut Domains<'_, T, P>,
+        required: &[usize],
+        close_others: bool,
+    ) -> Result<(), WaveError> {
+        let Some(root) = required.first().copied() else {
+            return Ok(());
+        };
+
+        let cells_total = self.open.len();
+        let directions_total = self.directions.len();
+
+        let mut is_required = vec![false; cells_total];
+        required.iter().for_each(|cell| is_required[*cell] = true);
+
+        // depth-first search from the root, finding the cells and the connections
+        // whose removal would separate some required cells from the root
+        let mut discovered = vec![usize::MAX; cells_total];
+        let mut low = vec![0; cells_total];
+        let mut below = vec![0; cells_total];
+        let mut parent = vec![None; cells_total];
+        let mut time = 0;
+
+        let mut opened = Vec::new();
+        let mut bridges = Vec::new();
+
+        discovered[root] = time;
+        low[root] = time;
+        below[root] = 1;
+
+        let mut stack = vec![(root, 0)];
+
+        while let Some((cell, next)) = stack.last_mut() {
+            let cell = *cell;
+
+            if *next < directions_total {
+                let direction = *next;
+                *next += 1;
+
+                let Some(other) = self.edge(domains, cell, direction) else {
+                    continue;
+                };
+
+                if discovered[other] == usize::MAX {
+                    time += 1;
+
+                    discovered[other] = time;
+                    low[other] = time;
+                    below[other] = usize::from(is_required[other]);
+                    parent[other] = Some((cell, direction));
+
+                    stack.push((other, 0));
+                } else if parent[cell]
+                    .is_some_and(|(p, d)| p == other && self.opposites[d] == Some(direction))
+                {
+                    continue;
+                } else {
+                    low[cell] = low[cell].min(discovered[other]);
+                }
+            } else {
+                let _ = stack.pop();
+
+                if let Some((p, direction)) = parent[cell] {
+                    low[p] = low[p].min(low[cell]);
+                    below[p] += below[cell];
+
+                    if below[cell] > 0 && low[cell] >= discovered[p] && !is_required[p] {
+                        opened.push(p);
+                    }
+
+                    if below[cell] > 0 && low[cell] > discovered[p] {
+                        bridges.push((p, direction, cell));
+                    }
+                }
+            }
+        }
+
+        if required.iter().any(|cell| discovered[*cell] == usize::MAX) {
+            return Err(WaveError::UncollapsibleWave);
+        }
+
+        for cell in opened {
+            self.retain(domains, cell, |mask| mask != 0)?;
+        }
+
+        for (cell, direction, other) in bridges {
+            let opposite = self.opposites[direction].unwrap();
+
+            self.retain(domains, cell, |mask| mask & 1 << direction != 0)?;
+            self.retain(domains, other, |mask| mask & 1 << opposite != 0)?;
+        }
+
+        if close_others {
+            let unreached = (0..cells_total)
+                .filter(|cell| discovered[*cell] == usize::MAX && self.open[*cell] != 0);
+
+            for cell in unreached {
+                self.retain(domains, cell, |mask| mask == 0)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Removes from the domain of the given cell the variants whose
+    /// connected directions don't satisfy `f`.
+    fn retain<T: Tile, P: Topology, F: Fn(u32) -> bool>(
+        &self,
+        domains: &mut Domains<'_, T, P>,
+        cell: usize,
+        f: F,
+    ) -> Result<(), WaveError> {
+        domains.retain_variants(cell, |variant| f(self.masks[variant]))
+    }
+}
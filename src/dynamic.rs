@@ -0,0 +1,300 @@
+//! Tile sets defined at runtime, where tiles are identified by an index and a name,
+//! which allows to load new tiles (for example from a rule file, or from a folder
+//! of images) without a Rust enum known at compile time.
+
+use crate::{
+    analysis::Analysis,
+    format::{FormatError, RawRuleFile, RuleFile},
+    matrix,
+    rules::RuleSet,
+    tile::Tile,
+    topology::{Grid, Topology},
+    wave::{self, Wave, WaveError},
+};
+
+use std::{fs, io, path::Path};
+
+/// Represents a tile of a [`DynamicTileSet`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DynamicTile(usize);
+
+impl DynamicTile {
+    /// Returns the index of the tile inside its tile set.
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+impl Tile for DynamicTile {}
+
+/// Represents a tile set defined at runtime, where each tile has a unique name and a glyph.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DynamicTileSet {
+    names: Vec<String>,
+    glyphs: Vec<String>,
+}
+
+impl DynamicTileSet {
+    /// Returns a new empty tile set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a new tile set with the file stems of the files inside
+    /// the given directory (such as a folder of images) as names.
+    pub fn from_dir<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut names = fs::read_dir(path)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<io::Result<Vec<_>>>()?
+            .into_iter()
+            .filter(|path| path.is_file())
+            .filter_map(|path| Some(path.file_stem()?.to_string_lossy().into_owned()))
+            .collect::<Vec<_>>();
+
+        names.sort();
+
+        let mut tile_set = Self::new();
+
+        names.iter().for_each(|name| {
+            let _ = tile_set.add(name);
+        });
+
+        Ok(tile_set)
+    }
+
+    /// Adds a tile with the given name, whose glyph is the first character of the name,
+    /// and returns it; if a tile with the same name already exists, it's returned instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::dynamic::DynamicTileSet;
+    /// let mut tile_set = DynamicTileSet::new();
+    ///
+    /// let grass = tile_set.add("grass");
+    /// let water = tile_set.add("water");
+    ///
+    /// assert_eq!(tile_set.add("grass"), grass);
+    /// assert_eq!(tile_set.name(water), "water");
+    /// assert_eq!(tile_set.glyph(water), "w");
+    /// ```
+    pub fn add(&mut self, name: &str) -> DynamicTile {
+        self.get(name).unwrap_or_else(|| {
+            self.names.push(name.to_string());
+            self.glyphs
+                .push(name.chars().next().map(String::from).unwrap_or_default());
+
+            DynamicTile(self.names.len() - 1)
+        })
+    }
+
+    /// Sets the glyph used to render the given tile.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tile doesn't belong to the tile set.
+    pub fn set_glyph(&mut self, tile: DynamicTile, glyph: &str) {
+        self.glyphs[tile.0] = glyph.to_string();
+    }
+
+    /// Returns the tile with the given name, if any.
+    pub fn get(&self, name: &str) -> Option<DynamicTile> {
+        self.names.iter().position(|n| n == name).map(DynamicTile)
+    }
+
+    /// Returns the name of the given tile.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tile doesn't belong to the tile set.
+    pub fn name(&self, tile: DynamicTile) -> &str {
+        &self.names[tile.0]
+    }
+
+    /// Returns the glyph of the given tile.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tile doesn't belong to the tile set.
+    pub fn glyph(&self, tile: DynamicTile) -> &str {
+        &self.glyphs[tile.0]
+    }
+
+    /// Returns the number of tiles in the tile set.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Returns `true` if and only if the tile set has no tiles.
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Returns the tiles of the tile set, in order of insertion.
+    pub fn tiles(&self) -> Vec<DynamicTile> {
+        (0..self.len()).map(DynamicTile).collect()
+    }
+
+    /// Parses a rule file from the given TOML text, adding every tile it mentions to the
+    /// tile set: this works exactly as [`RuleFile::from_toml`], but with runtime names.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{dynamic::DynamicTileSet, wave::Direction};
+    /// let mut tile_set = DynamicTileSet::new();
+    ///
+    /// let file = tile_set
+    ///     .load_toml(
+    ///         r#"
+    ///         [rules.grass]
+    ///         left = ["grass", "sand"]
+    ///         "#,
+    ///     )
+    ///     .unwrap();
+    ///
+    /// let (grass, sand) = (tile_set.get("grass").unwrap(), tile_set.get("sand").unwrap());
+    ///
+    /// assert!(file.rules.contains((grass, sand, Direction::Left)));
+    /// ```
+    pub fn load_toml(&mut self, text: &str) -> Result<RuleFile<DynamicTile>, FormatError> {
+        self.load(RawRuleFile::from_toml(text)?)
+    }
+
+    /// Parses a rule file from the given JSON text, adding every tile it mentions to the
+    /// tile set: this works exactly as [`RuleFile::from_json`], but with runtime names.
+    pub fn load_json(&mut self, text: &str) -> Result<RuleFile<DynamicTile>, FormatError> {
+        self.load(RawRuleFile::from_json(text)?)
+    }
+
+    /// Adds every tile mentioned in the given file, and resolves it.
+    fn load(&mut self, file: RawRuleFile) -> Result<RuleFile<DynamicTile>, FormatError> {
+        file.tile_names().into_iter().for_each(|name| {
+            let _ = self.add(name);
+        });
+
+        file.resolve(|name| self.get(name))
+    }
+
+    /// Returns the given rule file as TOML text, in the format read by [`DynamicTileSet::load_toml`].
+    pub fn to_toml(&self, file: &RuleFile<DynamicTile>) -> String {
+        RawRuleFile::from_rule_file(file, &self.tiles(), |tile| self.name(tile).to_string())
+            .to_toml()
+    }
+
+    /// Returns the given rule file as JSON text, in the format read by [`DynamicTileSet::load_json`].
+    pub fn to_json(&self, file: &RuleFile<DynamicTile>) -> String {
+        RawRuleFile::from_rule_file(file, &self.tiles(), |tile| self.name(tile).to_string())
+            .to_json()
+    }
+
+    /// Returns the given rules as adjacency matrices, rendering tiles through their glyphs,
+    /// failing as [`RuleSet::to_matrix`] does on glyphs that can't be read back.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{dynamic::DynamicTileSet, format::FormatError, rules::RuleSet};
+    /// let mut tile_set = DynamicTileSet::new();
+    ///
+    /// let wall = tile_set.add("wall");
+    ///
+    /// tile_set.set_glyph(wall, "##");
+    ///
+    /// assert_eq!(
+    ///     tile_set.to_matrix(&RuleSet::new()),
+    ///     Err(FormatError::UnknownTile("##".to_string()))
+    /// );
+    /// ```
+    pub fn to_matrix(&self, rules: &RuleSet<DynamicTile>) -> Result<String, FormatError> {
+        matrix::print(rules, &self.tiles(), |tile| self.glyph(*tile).to_string())
+    }
+
+    /// Parses rules from adjacency matrices, matching glyphs against the tiles of the tile set.
+    ///
+    /// Returns [`FormatError::AmbiguousTile`] if a glyph of the matrices is shared by more
+    /// than one tile, such as the default glyphs of `grass` and `gravel`: their glyphs
+    /// can be told apart through [`DynamicTileSet::set_glyph`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{dynamic::DynamicTileSet, format::FormatError, rules::RuleSet, wave::Direction};
+    /// let mut tile_set = DynamicTileSet::new();
+    ///
+    /// let grass = tile_set.add("grass");
+    /// let gravel = tile_set.add("gravel");
+    ///
+    /// let rules = RuleSet::new().allow(grass, gravel, Direction::Left);
+    ///
+    /// assert_eq!(
+    ///     tile_set.from_matrix("left\n |gg|\ng|.x|\ng|..|\n"),
+    ///     Err(FormatError::AmbiguousTile("g".to_string()))
+    /// );
+    ///
+    /// tile_set.set_glyph(gravel, "v");
+    ///
+    /// assert_eq!(tile_set.from_matrix(&tile_set.to_matrix(&rules).unwrap()), Ok(rules));
+    /// ```
+    pub fn from_matrix(&self, text: &str) -> Result<RuleSet<DynamicTile>, FormatError> {
+        matrix::parse(text, &self.tiles(), |tile| self.glyph(*tile).to_string())
+    }
+
+    /// Statically analyzes the given rules, as [`RuleSet::analyze`] does for enums.
+    pub fn analyze(&self, rules: &RuleSet<DynamicTile>) -> Analysis<DynamicTile> {
+        Analysis::of_grid(rules, &self.tiles())
+    }
+
+    /// Statically analyzes the given rules for the given topology,
+    /// as [`RuleSet::analyze_in`] does for enums.
+    pub fn analyze_in<P: Topology>(
+        &self,
+        rules: &RuleSet<DynamicTile>,
+        topology: &P,
+    ) -> Analysis<DynamicTile> {
+        Analysis::of(rules, &self.tiles(), topology)
+    }
+
+    /// Returns a new wave whose tiles can collapse to any tile of the tile set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{dynamic::DynamicTileSet, rules::RuleSet, wave::Direction};
+    /// # use rand::thread_rng;
+    /// let mut tile_set = DynamicTileSet::new();
+    ///
+    /// let grass = tile_set.add("grass");
+    ///
+    /// let rules = RuleSet::new()
+    ///     .allow_both_ways(grass, grass, Direction::Up)
+    ///     .allow_both_ways(grass, grass, Direction::Left);
+    ///
+    /// let mut wave = tile_set.wave(3, 2, rules).unwrap();
+    ///
+    /// wave.collapse(&mut thread_rng()).unwrap();
+    ///
+    /// assert_eq!(tile_set.render(&wave), "ggg\nggg\n");
+    /// ```
+    pub fn wave(
+        &self,
+        width: usize,
+        height: usize,
+        rules: RuleSet<DynamicTile>,
+    ) -> Result<Wave<DynamicTile>, WaveError> {
+        Wave::with_variants(width, height, self.tiles(), rules)
+    }
+
+    /// Returns the given wave rendered through the glyphs of the tiles, as the
+    /// [`Display`](std::fmt::Display) implementation of waves does for enums, where
+    /// tiles that haven't collapsed yet are rendered as `X`.
+    pub fn render<P: Grid>(&self, wave: &Wave<DynamicTile, P>) -> String {
+        let mut rendered = String::new();
+
+        let _ = wave::fmt_grid(&mut rendered, wave.topology(), |coords| {
+            wave.get(coords).map(|tile| self.glyph(tile))
+        });
+
+        rendered
+    }
+}
@@ -0,0 +1,569 @@
+//! Rule files, which describe the rules, weights, sockets and symmetries
+//! of a tile set in TOML or JSON, referring to tiles by their [`Tile::name`],
+//! or by their variant name for tiles without one:
+//!
+//! ```toml
+//! [rules.Empty]
+//! up = ["Empty", "HorizontalLine"]
+//! left = ["Empty"]
+//!
+//! [weights]
+//! Empty = 3.0
+//!
+//! [sockets]
+//! HorizontalLine = "0011"
+//!
+//! [symmetries.TopLeftCorner]
+//! horizontal = "TopRightCorner"
+//! vertical = "BottomLeftCorner"
+//! ```
+//!
+//! Sockets list one label for each direction, following the order `up`, `down`,
+//! `left`, `right`, and two tiles can be placed next to each other if and only
+//! if the labels of their touching sides are equal; other topologies can list
+//! their own directions, in the order followed by the sockets, before any table:
+//!
+//! ```toml
+//! directions = ["north_east", "east", "south_east", "south_west", "west", "north_west"]
+//!
+//! [sockets]
+//! Empty = "000000"
+//! ```
+
+use crate::{
+    rules::RuleSet,
+    tile::{self, Symmetry, Tile},
+    topology::Topology,
+    wave::{Direction, Wave, WaveError, DIRECTIONS_ORDER},
+};
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+};
+
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+
+/// Represents the content of a rule file, where tiles are referred to by name.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct RawRuleFile {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) directions: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) rules: BTreeMap<String, BTreeMap<String, Vec<String>>>,
+
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) weights: BTreeMap<String, f64>,
+
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) sockets: BTreeMap<String, String>,
+
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) symmetries: BTreeMap<String, RawSymmetry>,
+}
+
+/// Represents the mirrored tiles of a tile, referred to by name.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct RawSymmetry {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) horizontal: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) vertical: Option<String>,
+}
+
+impl RawRuleFile {
+    /// Parses the given TOML text.
+    pub(crate) fn from_toml(text: &str) -> Result<Self, FormatError> {
+        toml::from_str(text).map_err(|e| FormatError::Syntax(e.message().to_string()))
+    }
+
+    /// Parses the given JSON text.
+    pub(crate) fn from_json(text: &str) -> Result<Self, FormatError> {
+        serde_json::from_str(text).map_err(|e| FormatError::Syntax(e.to_string()))
+    }
+
+    /// Returns the file as TOML text.
+    pub(crate) fn to_toml(&self) -> String {
+        toml::to_string(self).expect("rule files can always be represented in TOML")
+    }
+
+    /// Returns the file as JSON text.
+    pub(crate) fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("rule files can always be represented in JSON")
+    }
+
+    /// Returns the names of every tile mentioned in the file, without duplicates.
+    pub(crate) fn tile_names(&self) -> Vec<&str> {
+        let mut names = Vec::new();
+
+        let mentioned = self
+            .rules
+            .iter()
+            .flat_map(|(name, directions)| {
+                std::iter::once(name).chain(directions.values().flatten())
+            })
+            .chain(self.weights.keys())
+            .chain(self.sockets.keys())
+            .chain(self.symmetries.iter().flat_map(|(name, symmetry)| {
+                std::iter::once(name)
+                    .chain(symmetry.horizontal.iter())
+                    .chain(symmetry.vertical.iter())
+            }));
+
+        for name in mentioned {
+            if !names.contains(&name.as_str()) {
+                names.push(name.as_str());
+            }
+        }
+
+        names
+    }
+
+    /// Converts the given [`RuleFile`] into a file, using `name` to refer to tiles by name:
+    /// tiles are sorted by name, while neighbours are listed following the order of `variants`.
+    pub(crate) fn from_rule_file<T: Tile, F: Fn(T) -> String>(
+        file: &RuleFile<T>,
+        variants: &[T],
+        name: F,
+    ) -> Self {
+        let rules = variants
+            .iter()
+            .map(|first_tile| {
+                let directions = Direction::iter()
+                    .map(|direction| {
+                        let neighbours = variants
+                            .iter()
+                            .filter(|second_tile| {
+                                file.rules.contains((*first_tile, **second_tile, direction))
+                            })
+                            .map(|second_tile| name(*second_tile))
+                            .collect::<Vec<_>>();
+
+                        (direction.to_string(), neighbours)
+                    })
+                    .filter(|(_, neighbours)| !neighbours.is_empty())
+                    .collect::<BTreeMap<_, _>>();
+
+                (name(*first_tile), directions)
+            })
+            .filter(|(_, directions)| !directions.is_empty())
+            .collect();
+
+        let symmetries = file
+            .symmetries
+            .iter()
+            .map(|(tile, symmetry)| {
+                let mirror = |mirrored: T| (mirrored != *tile).then(|| name(mirrored));
+
+                (
+                    name(*tile),
+                    RawSymmetry {
+                        horizontal: mirror(symmetry.horizontal),
+                        vertical: mirror(symmetry.vertical),
+                    },
+                )
+            })
+            .collect();
+
+        let directions = if file.directions == DIRECTIONS_ORDER {
+            Vec::new()
+        } else {
+            file.directions.iter().map(ToString::to_string).collect()
+        };
+
+        Self {
+            directions,
+            rules,
+            weights: file
+                .weights
+                .iter()
+                .map(|(tile, weight)| (name(*tile), *weight))
+                .collect(),
+            sockets: file
+                .sockets
+                .iter()
+                .map(|(tile, sockets)| (name(*tile), sockets.clone()))
+                .collect(),
+            symmetries,
+        }
+    }
+
+    /// Converts the file into a [`RuleFile`], using `tile` to look up tiles by name.
+    pub(crate) fn resolve<T: Tile, F: Fn(&str) -> Option<T>>(
+        &self,
+        tile: F,
+    ) -> Result<RuleFile<T>, FormatError> {
+        let lookup = |name: &str| tile(name).ok_or_else(|| FormatError::UnknownTile(name.into()));
+
+        let directions = if self.directions.is_empty() {
+            DIRECTIONS_ORDER.to_vec()
+        } else {
+            self.directions
+                .iter()
+                .map(|direction| {
+                    direction
+                        .parse::<Direction>()
+                        .map_err(|_| FormatError::UnknownDirection(direction.clone()))
+                })
+                .collect::<Result<_, _>>()?
+        };
+
+        let mut rules = RuleSet::new();
+
+        for (name, directions) in &self.rules {
+            let first_tile = lookup(name)?;
+
+            for (direction, neighbours) in directions {
+                let direction = direction
+                    .parse::<Direction>()
+                    .map_err(|_| FormatError::UnknownDirection(direction.clone()))?;
+
+                for neighbour in neighbours {
+                    let _ = rules.insert((first_tile, lookup(neighbour)?, direction));
+                }
+            }
+        }
+
+        let weights = self
+            .weights
+            .iter()
+            .map(|(name, weight)| {
+                if weight.is_finite() && *weight >= 0.0 {
+                    Ok((lookup(name)?, *weight))
+                } else {
+                    Err(FormatError::InvalidWeight(name.clone()))
+                }
+            })
+            .collect::<Result<_, _>>()?;
+
+        let sockets = self
+            .sockets
+            .iter()
+            .map(|(name, sockets)| {
+                if sockets.chars().count() == directions.len() {
+                    Ok((lookup(name)?, sockets.clone()))
+                } else {
+                    Err(FormatError::InvalidSockets(name.clone()))
+                }
+            })
+            .collect::<Result<_, _>>()?;
+
+        let symmetries = self
+            .symmetries
+            .iter()
+            .map(|(name, symmetry)| {
+                let tile = lookup(name)?;
+
+                let mirror =
+                    |mirrored: &Option<String>| mirrored.as_deref().map_or(Ok(tile), lookup);
+
+                Ok((
+                    tile,
+                    Symmetry {
+                        horizontal: mirror(&symmetry.horizontal)?,
+                        vertical: mirror(&symmetry.vertical)?,
+                    },
+                ))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(RuleFile {
+            directions,
+            rules,
+            weights,
+            sockets,
+            symmetries,
+        })
+    }
+}
+
+/// Represents the content of a rule file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleFile<T: Tile> {
+    /// The directions followed, in order, by the labels of the sockets,
+    /// which are `up`, `down`, `left` and `right` by default.
+    pub directions: Vec<Direction>,
+
+    /// The explicitly listed rules.
+    pub rules: RuleSet<T>,
+
+    /// The weight of each tile, tiles that are not listed have their [`Tile::weight`].
+    pub weights: HashMap<T, f64>,
+
+    /// The sockets of each tile, with one label for each of the directions.
+    pub sockets: HashMap<T, String>,
+
+    /// The mirrored tiles of each tile.
+    pub symmetries: HashMap<T, Symmetry<T>>,
+}
+
+impl<T: Tile + fmt::Debug + IntoEnumIterator> RuleFile<T> {
+    /// Parses a rule file from the given TOML text, where
+    /// tiles are referred to by the name of their variant.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{format::RuleFile, tile::MazeTile, wave::Direction};
+    /// let file = RuleFile::<MazeTile>::from_toml(
+    ///     r#"
+    ///     [rules.Empty]
+    ///     up = ["Empty", "HorizontalLine"]
+    ///
+    ///     [weights]
+    ///     Empty = 2.0
+    ///     "#,
+    /// )
+    /// .unwrap();
+    ///
+    /// assert!(file.rules.contains((MazeTile::Empty, MazeTile::HorizontalLine, Direction::Up)));
+    /// assert_eq!(file.weights[&MazeTile::Empty], 2.0);
+    /// ```
+    pub fn from_toml(text: &str) -> Result<Self, FormatError> {
+        RawRuleFile::from_toml(text)?.resolve(variant)
+    }
+
+    /// Parses a rule file from the given JSON text, where
+    /// tiles are referred to by the name of their variant.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{format::RuleFile, tile::MazeTile, wave::Direction};
+    /// let file = RuleFile::<MazeTile>::from_json(
+    ///     r#"{ "rules": { "Empty": { "up": ["Empty", "HorizontalLine"] } } }"#,
+    /// )
+    /// .unwrap();
+    ///
+    /// assert!(file.rules.contains((MazeTile::Empty, MazeTile::HorizontalLine, Direction::Up)));
+    /// ```
+    pub fn from_json(text: &str) -> Result<Self, FormatError> {
+        RawRuleFile::from_json(text)?.resolve(variant)
+    }
+
+    /// Returns the rule file as TOML text, with a stable ordering: tiles are sorted by name,
+    /// while neighbours are listed following the order of the variants.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{format::RuleFile, tile::MazeTile};
+    /// let file = RuleFile::<MazeTile>::from_toml(include_str!("../rules/maze.toml")).unwrap();
+    ///
+    /// assert_eq!(RuleFile::from_toml(&file.to_toml()), Ok(file));
+    /// ```
+    pub fn to_toml(&self) -> String {
+        RawRuleFile::from_rule_file(self, &T::iter().collect::<Vec<_>>(), |tile| {
+            tile::name(&tile)
+        })
+        .to_toml()
+    }
+
+    /// Returns the rule file as JSON text, with the same ordering as [`RuleFile::to_toml`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{format::RuleFile, tile::MazeTile};
+    /// let file = RuleFile::<MazeTile>::from_toml(include_str!("../rules/maze.toml")).unwrap();
+    ///
+    /// assert_eq!(RuleFile::from_json(&file.to_json()), Ok(file));
+    /// ```
+    pub fn to_json(&self) -> String {
+        RawRuleFile::from_rule_file(self, &T::iter().collect::<Vec<_>>(), |tile| {
+            tile::name(&tile)
+        })
+        .to_json()
+    }
+}
+
+impl<T: Tile + IntoEnumIterator> RuleFile<T> {
+    /// Returns the rule file described by the variants themselves, through their
+    /// [`Tile::weight`], [`Tile::sockets`] and [`Tile::symmetry`]; only weights other
+    /// than `1.0` and symmetries other than the variant itself are listed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{format::RuleFile, tile::MazeTile};
+    /// let file = RuleFile::<MazeTile>::from_tiles();
+    ///
+    /// assert_eq!(file.sockets[&MazeTile::CenterCross], "1111");
+    /// assert_eq!(file.symmetries[&MazeTile::TShaped].vertical, MazeTile::TShapedUpsideDown);
+    /// assert!(file.rules.is_empty());
+    /// ```
+    pub fn from_tiles() -> Self {
+        let mut file = Self::from(RuleSet::new());
+
+        for tile in T::iter() {
+            if tile.weight() != 1.0 {
+                let _ = file.weights.insert(tile, tile.weight());
+            }
+
+            if let Some(sockets) = tile.sockets() {
+                let _ = file.sockets.insert(tile, sockets.to_string());
+            }
+
+            let symmetry = tile.symmetry();
+
+            if symmetry.horizontal != tile || symmetry.vertical != tile {
+                let _ = file.symmetries.insert(tile, symmetry);
+            }
+        }
+
+        file
+    }
+
+    /// Returns a new wave that follows the rules and the weights of the file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{format::RuleFile, tile::MazeTile};
+    /// let file = RuleFile::<MazeTile>::from_toml(include_str!("../rules/maze.toml")).unwrap();
+    ///
+    /// let mut wave = file.wave(10, 10).unwrap();
+    ///
+    /// wave.set_weight(MazeTile::Empty, 2.0);
+    ///
+    /// // exporting the wave and reading it back gives an identical wave
+    /// let exported = RuleFile::from(&wave).to_toml();
+    ///
+    /// assert_eq!(RuleFile::from_toml(&exported).unwrap().wave(10, 10).unwrap(), wave);
+    /// ```
+    pub fn wave(&self, width: usize, height: usize) -> Result<Wave<T>, WaveError> {
+        self.wave_with_variants(width, height, T::iter().collect())
+    }
+}
+
+impl<T: Tile> RuleFile<T> {
+    /// Returns the explicitly listed rules, together with the rules derived from the sockets.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{format::RuleFile, tile::MazeTile, wave::Direction};
+    /// let file = RuleFile::<MazeTile>::from_toml(
+    ///     r#"
+    ///     [sockets]
+    ///     HorizontalLine = "0011"
+    ///     CenterCross = "1111"
+    ///     "#,
+    /// )
+    /// .unwrap();
+    ///
+    /// let rules = file.all_rules();
+    ///
+    /// assert!(rules.contains((MazeTile::HorizontalLine, MazeTile::CenterCross, Direction::Right)));
+    /// assert!(!rules.contains((MazeTile::HorizontalLine, MazeTile::CenterCross, Direction::Up)));
+    /// ```
+    ///
+    /// Sockets can also follow the directions of other topologies:
+    ///
+    /// ```
+    /// # use wfc::{format::RuleFile, tile::MazeTile, wave::Direction};
+    /// let file = RuleFile::<MazeTile>::from_toml(
+    ///     r#"
+    ///     directions = ["north_east", "east", "south_east", "south_west", "west", "north_west"]
+    ///
+    ///     [sockets]
+    ///     Empty = "000000"
+    ///     "#,
+    /// )
+    /// .unwrap();
+    ///
+    /// assert!(file.all_rules().contains((MazeTile::Empty, MazeTile::Empty, Direction::East)));
+    /// assert!(!file.all_rules().contains((MazeTile::Empty, MazeTile::Empty, Direction::Up)));
+    /// assert_eq!(RuleFile::from_toml(&file.to_toml()), Ok(file));
+    /// ```
+    pub fn all_rules(&self) -> RuleSet<T> {
+        self.rules.union(&RuleSet::from_sockets_in(
+            &self.directions,
+            self.sockets
+                .iter()
+                .map(|(tile, sockets)| (*tile, sockets.as_str())),
+        ))
+    }
+
+    /// Returns a new wave that follows the rules and the weights of the file,
+    /// whose tiles can collapse to the given variants.
+    pub fn wave_with_variants(
+        &self,
+        width: usize,
+        height: usize,
+        variants: Vec<T>,
+    ) -> Result<Wave<T>, WaveError> {
+        let mut wave = Wave::with_variants(width, height, variants, self.all_rules())?;
+
+        self.weights
+            .iter()
+            .for_each(|(tile, weight)| wave.set_weight(*tile, *weight));
+
+        Ok(wave)
+    }
+}
+
+impl<T: Tile> From<RuleSet<T>> for RuleFile<T> {
+    fn from(rules: RuleSet<T>) -> Self {
+        Self {
+            directions: DIRECTIONS_ORDER.to_vec(),
+            rules,
+            weights: HashMap::new(),
+            sockets: HashMap::new(),
+            symmetries: HashMap::new(),
+        }
+    }
+}
+
+impl<T: Tile, P: Topology> From<&Wave<T, P>> for RuleFile<T> {
+    /// Returns the rules and the weights of the given wave, so that
+    /// [`RuleFile::wave`] gives back an identical (uncollapsed) wave.
+    fn from(wave: &Wave<T, P>) -> Self {
+        Self {
+            weights: wave.weights().clone(),
+            ..Self::from(wave.rules().clone())
+        }
+    }
+}
+
+/// Returns the variant with the given name.
+fn variant<T: Tile + fmt::Debug + IntoEnumIterator>(name: &str) -> Option<T> {
+    T::iter().find(|tile| tile::name(tile) == name)
+}
+
+/// Represents possible errors that can occur while reading a rule file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormatError {
+    Syntax(String),
+    UnknownTile(String),
+    AmbiguousTile(String),
+    UnknownDirection(String),
+    InvalidWeight(String),
+    InvalidSockets(String),
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax(message) => writeln!(f, "Invalid syntax: {}", message),
+            Self::UnknownTile(name) => writeln!(f, "Unknown tile `{}`.", name),
+            Self::AmbiguousTile(name) => {
+                writeln!(f, "More than one tile is referred to as `{}`.", name)
+            }
+            Self::UnknownDirection(name) => writeln!(f, "Unknown direction `{}`.", name),
+            Self::InvalidWeight(name) => writeln!(f, "The weight of `{}` is invalid.", name),
+            Self::InvalidSockets(name) => writeln!(
+                f,
+                "The sockets of `{}` must have one label for each direction.",
+                name
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FormatError {}
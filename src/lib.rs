@@ -1,2 +1,15 @@
+extern crate self as wfc;
+
+pub mod analysis;
+mod bitset;
+pub mod chunk;
+pub mod constraint;
+pub mod dynamic;
+pub mod format;
+pub mod matrix;
+mod propagation;
+pub mod rules;
+pub mod sequence;
 pub mod tile;
+pub mod topology;
 pub mod wave;
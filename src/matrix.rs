@@ -0,0 +1,192 @@
+//! Adjacency matrices, a compact text format for rules: for each direction there is
+//! a grid with the tiles on both axes, rendered through their glyphs, with an `x`
+//! wherever the row tile allows the column tile next to it in that direction.
+//!
+//! ```text
+//! left
+//!  |━┃|
+//! ━|x.|
+//! ┃|..|
+//! ```
+//!
+//! Each glyph must be a single character, shared by no other tile, and rows and columns
+//! are delimited by `|` so that glyphs such as ` ` survive editors that strip trailing
+//! whitespace.
+
+use crate::{
+    format::FormatError,
+    rules::RuleSet,
+    tile::Tile,
+    wave::{Direction, DIRECTIONS_ORDER},
+};
+
+use std::fmt::Write;
+
+use strum::IntoEnumIterator;
+
+/// Represents the delimiter of rows and columns.
+const DELIMITER: char = '|';
+
+/// Represents an allowed adjacency.
+const ALLOWED: char = 'x';
+
+/// Represents a forbidden adjacency.
+const FORBIDDEN: char = '.';
+
+/// Returns the adjacency matrices of the given rules, one for each direction of a grid of
+/// square cells and for any other direction used by the rules, with the given variants
+/// on both axes, rendered through `glyph`, failing on glyphs that [`parse`] can't read
+/// back: glyphs that aren't a single character, and glyphs shared by more than one variant.
+pub(crate) fn print<T: Tile, F: Fn(&T) -> String>(
+    rules: &RuleSet<T>,
+    variants: &[T],
+    glyph: F,
+) -> Result<String, FormatError> {
+    let glyphs = variants.iter().map(glyph).collect::<Vec<_>>();
+
+    for (i, glyph) in glyphs.iter().enumerate() {
+        if glyph.chars().count() != 1 {
+            return Err(FormatError::UnknownTile(glyph.clone()));
+        }
+
+        if glyphs[..i].contains(glyph) {
+            return Err(FormatError::AmbiguousTile(glyph.clone()));
+        }
+    }
+
+    let mut matrix = String::new();
+
+    let directions = DIRECTIONS_ORDER.into_iter().chain(
+        Direction::iter()
+            .filter(|d| !DIRECTIONS_ORDER.contains(d) && rules.in_direction(*d).next().is_some()),
+    );
+
+    for (i, direction) in directions.enumerate() {
+        if i != 0 {
+            matrix.push('\n');
+        }
+
+        let _ = writeln!(matrix, "{}", direction);
+
+        let _ = write!(matrix, " {}", DELIMITER);
+
+        glyphs.iter().for_each(|glyph| matrix.push_str(glyph));
+
+        let _ = writeln!(matrix, "{}", DELIMITER);
+
+        for (first_tile, glyph) in variants.iter().copied().zip(&glyphs) {
+            let _ = write!(matrix, "{}{}", glyph, DELIMITER);
+
+            for second_tile in variants.iter().copied() {
+                matrix.push(if rules.contains((first_tile, second_tile, direction)) {
+                    ALLOWED
+                } else {
+                    FORBIDDEN
+                });
+            }
+
+            let _ = writeln!(matrix, "{}", DELIMITER);
+        }
+    }
+
+    Ok(matrix)
+}
+
+/// Parses the given adjacency matrices, whose glyphs are matched against the given
+/// variants through `glyph`, failing on glyphs shared by more than one variant.
+pub(crate) fn parse<T: Tile, F: Fn(&T) -> String>(
+    text: &str,
+    variants: &[T],
+    glyph: F,
+) -> Result<RuleSet<T>, FormatError> {
+    let glyphs = variants
+        .iter()
+        .map(|tile| (glyph(tile), *tile))
+        .collect::<Vec<_>>();
+
+    let tile = |glyph: char| {
+        let mut matching = glyphs
+            .iter()
+            .filter(|(g, _)| g.chars().eq(std::iter::once(glyph)))
+            .map(|(_, tile)| *tile);
+
+        match (matching.next(), matching.next()) {
+            (Some(tile), None) => Ok(tile),
+            (Some(_), Some(_)) => Err(FormatError::AmbiguousTile(glyph.to_string())),
+            (None, _) => Err(FormatError::UnknownTile(glyph.to_string())),
+        }
+    };
+
+    let mut rules = RuleSet::new();
+
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim_end()))
+        .filter(|(_, line)| !line.is_empty());
+
+    while let Some((_, name)) = lines.next() {
+        let direction = name
+            .trim()
+            .parse::<Direction>()
+            .map_err(|_| FormatError::UnknownDirection(name.trim().to_string()))?;
+
+        let (number, header) = lines
+            .next()
+            .ok_or_else(|| FormatError::Syntax(format!("missing header after `{}`", name)))?;
+
+        let columns = cells(number, header)?
+            .1
+            .into_iter()
+            .map(tile)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for _ in 0..columns.len() {
+            let (number, line) = lines
+                .next()
+                .ok_or_else(|| FormatError::Syntax(format!("missing rows after `{}`", name)))?;
+
+            let (glyph, row) = cells(number, line)?;
+
+            if row.len() != columns.len() {
+                return Err(FormatError::Syntax(format!(
+                    "line {}: expected {} columns",
+                    number,
+                    columns.len()
+                )));
+            }
+
+            let first_tile = tile(glyph)?;
+
+            for (second_tile, cell) in columns.iter().zip(row) {
+                match cell {
+                    ALLOWED => {
+                        let _ = rules.insert((first_tile, *second_tile, direction));
+                    }
+                    FORBIDDEN => {}
+                    _ => {
+                        return Err(FormatError::Syntax(format!(
+                            "line {}: unexpected `{}`",
+                            number, cell
+                        )))
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(rules)
+}
+
+/// Splits a line of the form `<glyph>|<cells>|` into the glyph and the cells.
+fn cells(number: usize, line: &str) -> Result<(char, Vec<char>), FormatError> {
+    let chars = line.chars().collect::<Vec<_>>();
+
+    match chars.as_slice() {
+        [glyph, DELIMITER, cells @ .., DELIMITER] => Ok((*glyph, cells.to_vec())),
+        _ => Err(FormatError::Syntax(format!(
+            "line {}: expected `<glyph>{}<cells>{}`",
+            number, DELIMITER, DELIMITER
+        ))),
+    }
+}
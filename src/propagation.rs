@@ -0,0 +1,195 @@
+//! Constraint propagation over cells and variants identified by dense indices, where the
+//! variants each cell can still collapse to are represented by a [`BitSet`], which keeps
+//! the cost of propagation independent of how the variants are enumerated.
+
+use crate::{bitset::BitSet, rules::RuleSet, tile::Tile, topology::Topology};
+
+use std::collections::HashMap;
+
+use rand::{
+    seq::{IteratorRandom, SliceRandom},
+    Rng,
+};
+
+/// Represents rules compiled to the indices of the variants and of the directions.
+#[derive(Debug, Clone)]
+pub(crate) struct Adjacency {
+    variants_total: usize,
+    allowed: Vec<BitSet>,
+    allowed_by_any: Vec<BitSet>,
+}
+
+impl Adjacency {
+    /// Compiles the given rules for the given variants and the directions of the given
+    /// topology: as each tile checks the rules against its own neighbours, a variant can
+    /// be placed next to another one in a direction if and only if the rules contain both
+    /// `(variant, other, direction)` and `(other, variant, opposite)`, where the opposite
+    /// direction is the one of the topology, and rules about other variants or directions
+    /// are ignored.
+    pub(crate) fn new<T: Tile, P: Topology>(
+        rules: &RuleSet<T>,
+        variants: &[T],
+        topology: &P,
+    ) -> Self {
+        let directions = topology.directions();
+        let variants_total = variants.len();
+
+        let indices = variants
+            .iter()
+            .enumerate()
+            .map(|(i, tile)| (*tile, i))
+            .collect::<HashMap<_, _>>();
+
+        let direction_index = |direction| directions.iter().position(|d| *d == direction);
+
+        let mut allowed = vec![BitSet::empty(variants_total); directions.len() * variants_total];
+
+        for (first_tile, second_tile, direction) in rules.iter() {
+            let (Some(first), Some(second), Some(forward), Some(_)) = (
+                indices.get(&first_tile),
+                indices.get(&second_tile),
+                direction_index(direction),
+                direction_index(topology.opposite(direction)),
+            ) else {
+                continue;
+            };
+
+            if rules.contains((second_tile, first_tile, topology.opposite(direction))) {
+                allowed[forward * variants_total + first].insert(*second);
+            }
+        }
+
+        let allowed_by_any = (0..directions.len())
+            .map(|direction| {
+                let mut any = BitSet::empty(variants_total);
+
+                allowed[direction * variants_total..(direction + 1) * variants_total]
+                    .iter()
+                    .for_each(|set| any.union_with(set));
+
+                any
+            })
+            .collect();
+
+        Self {
+            variants_total,
+            allowed,
+            allowed_by_any,
+        }
+    }
+
+    /// Returns the variants that can be placed in the given direction of at least one of the
+    /// given variants, where the direction is identified by its index in the compiled order.
+    pub(crate) fn allowed(&self, direction: usize, variants: &BitSet) -> BitSet {
+        if variants.is_full() {
+            return self.allowed_by_any[direction].clone();
+        }
+
+        let mut allowed = BitSet::empty(self.variants_total);
+
+        variants
+            .iter()
+            .for_each(|v| allowed.union_with(&self.allowed[direction * self.variants_total + v]));
+
+        allowed
+    }
+}
+
+/// Represents the neighbours of each cell of a topology, computed once.
+#[derive(Debug, Clone)]
+pub(crate) struct Neighbours {
+    directions_total: usize,
+    neighbours: Vec<Option<usize>>,
+}
+
+impl Neighbours {
+    /// Returns the neighbours of each cell of the given topology,
+    /// following the order of the directions of the topology.
+    pub(crate) fn new<P: Topology>(topology: &P) -> Self {
+        let directions = topology.directions();
+
+        Self {
+            directions_total: directions.len(),
+            neighbours: (0..topology.cells_total())
+                .flat_map(|cell| directions.iter().map(move |d| topology.neighbour(cell, *d)))
+                .collect(),
+        }
+    }
+
+    /// Returns the neighbour of the given cell in the direction with the given index, if any.
+    pub(crate) fn get(&self, cell: usize, direction: usize) -> Option<usize> {
+        self.neighbours[cell * self.directions_total + direction]
+    }
+}
+
+/// Removes from the domains of the cells every variant that can't be placed next to
+/// the variants of their neighbours, starting from the given changed cells.
+///
+/// Returns the first cell left without variants, if any, as an error.
+pub(crate) fn propagate(
+    domains: &mut [BitSet],
+    adjacency: &Adjacency,
+    neighbours: &Neighbours,
+    changed: Vec<usize>,
+) -> Result<(), usize> {
+    let mut queued = vec![false; domains.len()];
+
+    changed.iter().for_each(|cell| queued[*cell] = true);
+
+    let mut stack = changed;
+
+    while let Some(cell) = stack.pop() {
+        queued[cell] = false;
+
+        for direction in 0..neighbours.directions_total {
+            let Some(other) = neighbours.get(cell, direction) else {
+                continue;
+            };
+
+            let allowed = adjacency.allowed(direction, &domains[cell]);
+
+            if domains[other].intersect_with(&allowed) {
+                if domains[other].is_empty() {
+                    return Err(other);
+                }
+
+                if !queued[other] {
+                    queued[other] = true;
+                    stack.push(other);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns a random cell among the ones that haven't collapsed yet
+/// with the fewest variants left, if any.
+pub(crate) fn lowest_entropy<R: Rng + ?Sized>(domains: &[BitSet], rng: &mut R) -> Option<usize> {
+    let lengths = domains.iter().map(BitSet::len).collect::<Vec<_>>();
+
+    let lowest = lengths.iter().copied().filter(|len| *len > 1).min()?;
+
+    lengths
+        .iter()
+        .enumerate()
+        .filter(|(_, len)| **len == lowest)
+        .map(|(cell, _)| cell)
+        .choose(rng)
+}
+
+/// Returns a random variant among the given ones, following
+/// the given weights, indexed by variant.
+pub(crate) fn choose_variant<R: Rng + ?Sized>(
+    variants: &BitSet,
+    weights: &[f64],
+    rng: &mut R,
+) -> Option<usize> {
+    variants
+        .iter()
+        .collect::<Vec<_>>()
+        .choose_weighted(rng, |variant| weights[*variant])
+        .ok()
+        .copied()
+}
@@ -0,0 +1,593 @@
+use crate::{
+    analysis::{self, Analysis},
+    format::{FormatError, RuleFile},
+    matrix,
+    tile::{self, Tile},
+    topology::{Moore, Square, Topology},
+    wave::{Direction, DIRECTIONS_ORDER},
+};
+
+use std::{
+    collections::{hash_set, HashSet},
+    fmt,
+};
+
+use strum::IntoEnumIterator;
+
+/// Represents a set of adjacency rules, where each rule `(first_tile, second_tile, direction)`
+/// indicates that `first_tile` can be placed next to `second_tile` in the `direction` direction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleSet<T: Tile> {
+    rules: HashSet<(T, T, Direction)>,
+}
+
+impl<T: Tile> RuleSet<T> {
+    /// Returns a new empty rule set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{rules::RuleSet, tile::MazeTile};
+    /// let rules = RuleSet::<MazeTile>::new();
+    ///
+    /// assert!(rules.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        Self {
+            rules: HashSet::new(),
+        }
+    }
+
+    /// Returns the rules derived from the given sockets, where each sockets string has
+    /// one label for each direction, following the order `up`, `down`, `left`, `right`:
+    /// two tiles can be placed next to each other if and only if the labels
+    /// of their touching sides are equal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{rules::RuleSet, tile::MazeTile, wave::Direction};
+    /// let rules = RuleSet::from_sockets([(MazeTile::TShaped, "0111"), (MazeTile::Empty, "0000")]);
+    ///
+    /// assert!(rules.contains((MazeTile::TShaped, MazeTile::Empty, Direction::Up)));
+    /// assert!(!rules.contains((MazeTile::TShaped, MazeTile::Empty, Direction::Down)));
+    /// ```
+    pub fn from_sockets<'a, I: IntoIterator<Item = (T, &'a str)>>(sockets: I) -> Self {
+        Self::from_sockets_in(&DIRECTIONS_ORDER, sockets)
+    }
+
+    /// Returns the rules derived from the given sockets, as [`RuleSet::from_sockets`] does,
+    /// where each sockets string has one label for each of the given directions, in order,
+    /// so that topologies other than grids of square cells can be described too.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any sockets string doesn't have exactly one label for each direction.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{rules::RuleSet, tile::MazeTile, wave::Direction};
+    /// let directions = [Direction::Up, Direction::Down, Direction::North, Direction::South];
+    ///
+    /// let rules = RuleSet::from_sockets_in(
+    ///     &directions,
+    ///     [(MazeTile::VerticalLine, "1100"), (MazeTile::Empty, "0000")],
+    /// );
+    ///
+    /// assert!(rules.contains((MazeTile::VerticalLine, MazeTile::VerticalLine, Direction::Up)));
+    /// assert!(rules.contains((MazeTile::VerticalLine, MazeTile::Empty, Direction::North)));
+    /// assert!(!rules.contains((MazeTile::VerticalLine, MazeTile::Empty, Direction::Up)));
+    /// ```
+    pub fn from_sockets_in<'a, I: IntoIterator<Item = (T, &'a str)>>(
+        directions: &[Direction],
+        sockets: I,
+    ) -> Self {
+        let sockets = sockets
+            .into_iter()
+            .map(|(tile, labels)| (tile, labels.chars().collect::<Vec<_>>()))
+            .collect::<Vec<_>>();
+
+        assert!(
+            sockets
+                .iter()
+                .all(|(_, labels)| labels.len() == directions.len()),
+            "the sockets must have one label for each direction"
+        );
+
+        sockets
+            .iter()
+            .flat_map(|(first_tile, first_labels)| {
+                sockets
+                    .iter()
+                    .flat_map(move |(second_tile, second_labels)| {
+                        directions
+                            .iter()
+                            .enumerate()
+                            .filter(move |(i, direction)| {
+                                let opposite =
+                                    directions.iter().position(|d| *d == direction.opposite());
+
+                                first_labels.get(*i).is_some()
+                                    && opposite.is_some_and(|opposite| {
+                                        first_labels.get(*i) == second_labels.get(opposite)
+                                    })
+                            })
+                            .map(move |(_, direction)| (*first_tile, *second_tile, *direction))
+                    })
+            })
+            .collect()
+    }
+
+    /// Allows `second_tile` to be placed next to `first_tile` in the `direction` direction.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{rules::RuleSet, tile::MazeTile, wave::Direction};
+    /// let rules = RuleSet::new()
+    ///     .allow(MazeTile::Empty, MazeTile::Empty, Direction::Up)
+    ///     .allow(MazeTile::Empty, MazeTile::Empty, Direction::Down);
+    ///
+    /// assert_eq!(rules.len(), 2);
+    /// ```
+    pub fn allow(mut self, first_tile: T, second_tile: T, direction: Direction) -> Self {
+        let _ = self.insert((first_tile, second_tile, direction));
+
+        self
+    }
+
+    /// Allows every rule yielded by the given iterator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{rules::RuleSet, tile::MazeTile, wave::Direction};
+    /// let rules = RuleSet::new().allow_all([
+    ///     (MazeTile::Empty, MazeTile::VerticalLine, Direction::Left),
+    ///     (MazeTile::Empty, MazeTile::VerticalLine, Direction::Right),
+    /// ]);
+    ///
+    /// assert_eq!(rules.len(), 2);
+    /// ```
+    pub fn allow_all<I: IntoIterator<Item = (T, T, Direction)>>(mut self, rules: I) -> Self {
+        self.extend(rules);
+
+        self
+    }
+
+    /// Allows `second_tile` to be placed next to `first_tile` in the `direction` direction,
+    /// and `first_tile` to be placed next to `second_tile` in the opposite direction.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{rules::RuleSet, tile::MazeTile, wave::Direction};
+    /// let rules = RuleSet::new().allow_both_ways(
+    ///     MazeTile::Empty,
+    ///     MazeTile::VerticalLine,
+    ///     Direction::Left,
+    /// );
+    ///
+    /// assert!(rules.contains((MazeTile::VerticalLine, MazeTile::Empty, Direction::Right)));
+    /// ```
+    pub fn allow_both_ways(self, first_tile: T, second_tile: T, direction: Direction) -> Self {
+        self.allow(first_tile, second_tile, direction).allow(
+            second_tile,
+            first_tile,
+            direction.opposite(),
+        )
+    }
+
+    /// Forbids `second_tile` to be placed next to `first_tile` in the `direction` direction.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{rules::RuleSet, tile::MazeTile, wave::Direction};
+    /// let rules = RuleSet::new()
+    ///     .allow(MazeTile::Empty, MazeTile::Empty, Direction::Up)
+    ///     .forbid(MazeTile::Empty, MazeTile::Empty, Direction::Up);
+    ///
+    /// assert!(rules.is_empty());
+    /// ```
+    pub fn forbid(mut self, first_tile: T, second_tile: T, direction: Direction) -> Self {
+        let _ = self.remove((first_tile, second_tile, direction));
+
+        self
+    }
+
+    /// Inserts the given rule, returning `true` if and only if it was not already present.
+    pub fn insert(&mut self, rule: (T, T, Direction)) -> bool {
+        self.rules.insert(rule)
+    }
+
+    /// Removes the given rule, returning `true` if and only if it was present.
+    pub fn remove(&mut self, rule: (T, T, Direction)) -> bool {
+        self.rules.remove(&rule)
+    }
+
+    /// Returns `true` if and only if the given rule is in the set.
+    pub fn contains(&self, rule: (T, T, Direction)) -> bool {
+        self.rules.contains(&rule)
+    }
+
+    /// Returns the number of rules in the set.
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    /// Returns `true` if and only if the set contains no rules.
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Returns an iterator over the rules of the set, in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (T, T, Direction)> + '_ {
+        self.rules.iter().copied()
+    }
+
+    /// Returns an iterator over the rules whose first tile is `tile`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{rules::RuleSet, tile::MazeTile, wave::Direction};
+    /// let rules = RuleSet::new()
+    ///     .allow(MazeTile::Empty, MazeTile::Empty, Direction::Up)
+    ///     .allow(MazeTile::CenterCross, MazeTile::VerticalLine, Direction::Up);
+    ///
+    /// assert_eq!(rules.for_tile(MazeTile::Empty).count(), 1);
+    /// ```
+    pub fn for_tile(&self, tile: T) -> impl Iterator<Item = (T, T, Direction)> + '_ {
+        self.iter()
+            .filter(move |(first_tile, _, _)| *first_tile == tile)
+    }
+
+    /// Returns an iterator over the rules in the `direction` direction.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{rules::RuleSet, tile::MazeTile, wave::Direction};
+    /// let rules = RuleSet::new()
+    ///     .allow(MazeTile::Empty, MazeTile::Empty, Direction::Up)
+    ///     .allow(MazeTile::Empty, MazeTile::Empty, Direction::Down);
+    ///
+    /// assert_eq!(rules.in_direction(Direction::Down).count(), 1);
+    /// ```
+    pub fn in_direction(
+        &self,
+        direction: Direction,
+    ) -> impl Iterator<Item = (T, T, Direction)> + '_ {
+        self.iter().filter(move |(_, _, d)| *d == direction)
+    }
+
+    /// Returns a new rule set with the rules that are in either `self` or `other`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{rules::RuleSet, tile::MazeTile, wave::Direction};
+    /// let vertical = RuleSet::new().allow(MazeTile::Empty, MazeTile::Empty, Direction::Up);
+    /// let horizontal = RuleSet::new().allow(MazeTile::Empty, MazeTile::Empty, Direction::Left);
+    ///
+    /// assert_eq!(vertical.union(&horizontal).len(), 2);
+    /// ```
+    pub fn union(&self, other: &Self) -> Self {
+        self.rules.union(&other.rules).copied().collect()
+    }
+
+    /// Returns a new rule set with the rules that are in both `self` and `other`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{rules::RuleSet, tile::MazeTile, wave::Direction};
+    /// let first = RuleSet::new()
+    ///     .allow(MazeTile::Empty, MazeTile::Empty, Direction::Up)
+    ///     .allow(MazeTile::Empty, MazeTile::Empty, Direction::Left);
+    /// let second = RuleSet::new().allow(MazeTile::Empty, MazeTile::Empty, Direction::Left);
+    ///
+    /// assert_eq!(first.intersection(&second), second);
+    /// ```
+    pub fn intersection(&self, other: &Self) -> Self {
+        self.rules.intersection(&other.rules).copied().collect()
+    }
+
+    /// Returns a new rule set with the rules that are in `self` but not in `other`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{rules::RuleSet, tile::MazeTile, wave::Direction};
+    /// let first = RuleSet::new()
+    ///     .allow(MazeTile::Empty, MazeTile::Empty, Direction::Up)
+    ///     .allow(MazeTile::Empty, MazeTile::Empty, Direction::Left);
+    /// let second = RuleSet::new().allow(MazeTile::Empty, MazeTile::Empty, Direction::Left);
+    ///
+    /// assert!(first.difference(&second).contains((MazeTile::Empty, MazeTile::Empty, Direction::Up)));
+    /// ```
+    pub fn difference(&self, other: &Self) -> Self {
+        self.rules.difference(&other.rules).copied().collect()
+    }
+}
+
+impl<A: Tile, B: Tile> RuleSet<(A, B)> {
+    /// Returns the rules of stacks of two layers, where a stack can be placed next to
+    /// another one in a direction if and only if the tiles of each layer can, following
+    /// the rules of that layer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{rules::RuleSet, tile::MazeTile, wave::Direction};
+    /// let floor =
+    ///     RuleSet::new().allow_both_ways(MazeTile::Empty, MazeTile::CenterCross, Direction::Right);
+    /// let decoration =
+    ///     RuleSet::new().allow_both_ways(MazeTile::Empty, MazeTile::Empty, Direction::Left);
+    ///
+    /// let rules = RuleSet::layered(&floor, &decoration);
+    ///
+    /// assert!(rules.contains((
+    ///     (MazeTile::Empty, MazeTile::Empty),
+    ///     (MazeTile::CenterCross, MazeTile::Empty),
+    ///     Direction::Right
+    /// )));
+    /// assert_eq!(rules.len(), 2);
+    /// ```
+    pub fn layered(first: &RuleSet<A>, second: &RuleSet<B>) -> Self {
+        first
+            .iter()
+            .flat_map(|(lower, other_lower, direction)| {
+                second
+                    .in_direction(direction)
+                    .map(move |(upper, other_upper, _)| {
+                        ((lower, upper), (other_lower, other_upper), direction)
+                    })
+            })
+            .collect()
+    }
+}
+
+impl<T: Tile + IntoEnumIterator> RuleSet<T> {
+    /// Returns the rules derived from the [`Tile::sockets`] of the variants,
+    /// as [`RuleSet::from_sockets`] does; variants without sockets get no rules.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{rules::RuleSet, tile::MazeTile, wave::Direction};
+    /// let rules = RuleSet::<MazeTile>::from_tiles();
+    ///
+    /// assert!(rules.contains((MazeTile::HorizontalLine, MazeTile::CenterCross, Direction::Right)));
+    /// assert!(!rules.contains((MazeTile::HorizontalLine, MazeTile::VerticalLine, Direction::Right)));
+    /// ```
+    pub fn from_tiles() -> Self {
+        Self::from_tiles_in(&DIRECTIONS_ORDER)
+    }
+
+    /// Returns the rules derived from the [`Tile::sockets`] of the variants, as
+    /// [`RuleSet::from_sockets_in`] does for the given directions; variants without
+    /// sockets get no rules.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the sockets of any variant don't have exactly one label for each direction.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{rules::RuleSet, tile::Tile, topology::{Hex, Topology}, wave::Direction};
+    /// # use strum_macros::EnumIter;
+    /// #[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, Hash, Tile)]
+    /// enum River {
+    ///     #[tile(sockets = "010010")]
+    ///     Straight,
+    ///
+    ///     #[tile(sockets = "000000")]
+    ///     Land,
+    /// }
+    ///
+    /// let rules = RuleSet::<River>::from_tiles_in(Hex::new(1, 1).directions());
+    ///
+    /// assert!(rules.contains((River::Straight, River::Straight, Direction::East)));
+    /// assert!(rules.contains((River::Straight, River::Land, Direction::NorthEast)));
+    /// assert!(!rules.contains((River::Straight, River::Land, Direction::East)));
+    /// ```
+    pub fn from_tiles_in(directions: &[Direction]) -> Self {
+        Self::from_sockets_in(
+            directions,
+            T::iter().filter_map(|tile| Some((tile, tile.sockets()?))),
+        )
+    }
+
+    /// Statically analyzes the rules, reporting variants that can never appear,
+    /// directions without rules, dead-end variants, missing inverse rules and whether
+    /// a small grid can be consistently tiled, before even collapsing a wave.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{rules::RuleSet, tile::MazeTile, wave::Direction};
+    /// let rules = RuleSet::new()
+    ///     .allow_both_ways(MazeTile::Empty, MazeTile::Empty, Direction::Up)
+    ///     .allow_both_ways(MazeTile::Empty, MazeTile::Empty, Direction::Left);
+    ///
+    /// let analysis = rules.analyze();
+    ///
+    /// assert!(analysis.unplaceable.contains(&MazeTile::CenterCross));
+    /// assert!(analysis.missing_inverses.is_empty());
+    /// assert!(analysis.tiling.is_some());
+    ///
+    /// // diagonal neighbours are only checked once a rule refers to them
+    /// let analysis = rules.allow(MazeTile::Empty, MazeTile::Empty, Direction::UpLeft).analyze();
+    ///
+    /// assert_eq!(analysis.empty_axes, [(Direction::UpRight, Direction::DownLeft)]);
+    /// assert!(analysis.tiling.is_none());
+    /// ```
+    pub fn analyze(&self) -> Analysis<T> {
+        Analysis::of_grid(self, &T::iter().collect::<Vec<_>>())
+    }
+
+    /// Statically analyzes the rules as [`RuleSet::analyze`] does, for waves laid out
+    /// following the given topology: only its directions are taken into account, and
+    /// a consistent tiling of its cells is looked for, so it should be small.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{rules::RuleSet, tile::MazeTile, topology::Hex, wave::Direction};
+    /// let rules = [Direction::NorthEast, Direction::East, Direction::SouthEast]
+    ///     .into_iter()
+    ///     .fold(RuleSet::new(), |rules, direction| {
+    ///         rules.allow_both_ways(MazeTile::Empty, MazeTile::Empty, direction)
+    ///     });
+    ///
+    /// let analysis = rules.analyze_in(&Hex::new(3, 3));
+    ///
+    /// assert!(!analysis.unplaceable.contains(&MazeTile::Empty));
+    /// assert!(analysis.empty_axes.is_empty());
+    /// assert_eq!(analysis.tiling.map(|tiling| tiling.len()), Some(9));
+    ///
+    /// // the same rules are useless for a grid of square cells
+    /// assert!(rules.analyze().unplaceable.contains(&MazeTile::Empty));
+    /// ```
+    pub fn analyze_in<P: Topology>(&self, topology: &P) -> Analysis<T> {
+        Analysis::of(self, &T::iter().collect::<Vec<_>>(), topology)
+    }
+
+    /// Returns a consistent tiling of a `width` by `height` grid, if any, found through
+    /// an exhaustive search: this is meant to be used on small grids only.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{rules::RuleSet, tile::MazeTile, wave::Direction};
+    /// let rules = RuleSet::new()
+    ///     .allow_both_ways(MazeTile::HorizontalLine, MazeTile::HorizontalLine, Direction::Left);
+    ///
+    /// assert!(rules.find_tiling(3, 1).is_some());
+    /// assert!(rules.find_tiling(3, 2).is_none());
+    /// ```
+    pub fn find_tiling(&self, width: usize, height: usize) -> Option<Vec<Vec<T>>> {
+        let variants = T::iter().collect::<Vec<_>>();
+
+        let tiling = if analysis::is_diagonal(self) {
+            analysis::find_tiling(self, &variants, &Moore::new(width, height))
+        } else {
+            analysis::find_tiling(self, &variants, &Square::new(width, height))
+        };
+
+        tiling.map(|tiling| tiling.chunks(width).map(<[T]>::to_vec).collect())
+    }
+}
+
+impl<T: Tile + fmt::Debug + IntoEnumIterator> RuleSet<T> {
+    /// Returns the rules as TOML text, with a stable ordering, in the format read by
+    /// [`RuleFile::from_toml`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{rules::RuleSet, tile::MazeTile, wave::Direction};
+    /// let rules = RuleSet::new()
+    ///     .allow(MazeTile::Empty, MazeTile::HorizontalLine, Direction::Up)
+    ///     .allow(MazeTile::Empty, MazeTile::Empty, Direction::Up);
+    ///
+    /// assert_eq!(rules.to_toml(), "[rules.Empty]\nup = [\"HorizontalLine\", \"Empty\"]\n");
+    /// ```
+    pub fn to_toml(&self) -> String {
+        RuleFile::from(self.clone()).to_toml()
+    }
+
+    /// Returns the rules as JSON text, with a stable ordering, in the format read by
+    /// [`RuleFile::from_json`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{format::RuleFile, rules::RuleSet, tile::MazeTile, wave::Direction};
+    /// let rules = RuleSet::new().allow(MazeTile::Empty, MazeTile::Empty, Direction::Up);
+    ///
+    /// assert_eq!(RuleFile::from_json(&rules.to_json()).unwrap().rules, rules);
+    /// ```
+    pub fn to_json(&self) -> String {
+        RuleFile::from(self.clone()).to_json()
+    }
+}
+
+impl<T: Tile + fmt::Display + IntoEnumIterator> RuleSet<T> {
+    /// Returns the rules as adjacency matrices, one for each direction, where the tiles
+    /// are rendered through their glyphs and an `x` marks every allowed adjacency.
+    ///
+    /// Returns [`FormatError::UnknownTile`] if the glyph of a tile isn't a single character,
+    /// or [`FormatError::AmbiguousTile`] if it's shared by more than one tile, since
+    /// [`RuleSet::from_matrix`] couldn't read them back.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{rules::RuleSet, tile::MazeTile, wave::Direction};
+    /// let rules = RuleSet::new().allow(MazeTile::Empty, MazeTile::CenterCross, Direction::Up);
+    /// let matrix = rules.to_matrix().unwrap();
+    ///
+    /// assert!(matrix.starts_with("up\n |┳┫┣┻┓┏┗┛━┃╋ |\n┳|............|\n"));
+    /// assert!(matrix.contains("\n |..........x.|\n\ndown\n"));
+    /// ```
+    pub fn to_matrix(&self) -> Result<String, FormatError> {
+        matrix::print(self, &T::iter().collect::<Vec<_>>(), tile::glyph)
+    }
+
+    /// Parses rules from adjacency matrices, in the format returned by [`RuleSet::to_matrix`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{rules::RuleSet, tile::MazeTile, wave::Direction};
+    /// let rules = RuleSet::<MazeTile>::from_matrix("left\n |━┃|\n━|x.|\n┃|..|\n").unwrap();
+    ///
+    /// assert!(rules.contains((MazeTile::HorizontalLine, MazeTile::HorizontalLine, Direction::Left)));
+    /// assert_eq!(rules.len(), 1);
+    ///
+    /// assert_eq!(RuleSet::from_matrix(&rules.to_matrix().unwrap()), Ok(rules));
+    /// ```
+    pub fn from_matrix(text: &str) -> Result<Self, FormatError> {
+        matrix::parse(text, &T::iter().collect::<Vec<_>>(), tile::glyph)
+    }
+}
+
+impl<T: Tile> Default for RuleSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Tile> From<HashSet<(T, T, Direction)>> for RuleSet<T> {
+    fn from(rules: HashSet<(T, T, Direction)>) -> Self {
+        Self { rules }
+    }
+}
+
+impl<T: Tile> FromIterator<(T, T, Direction)> for RuleSet<T> {
+    fn from_iter<I: IntoIterator<Item = (T, T, Direction)>>(iter: I) -> Self {
+        Self {
+            rules: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<T: Tile> Extend<(T, T, Direction)> for RuleSet<T> {
+    fn extend<I: IntoIterator<Item = (T, T, Direction)>>(&mut self, iter: I) {
+        self.rules.extend(iter)
+    }
+}
+
+impl<T: Tile> IntoIterator for RuleSet<T> {
+    type Item = (T, T, Direction);
+    type IntoIter = hash_set::IntoIter<(T, T, Direction)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.rules.into_iter()
+    }
+}
@@ -0,0 +1,109 @@
+//! Unbounded sequences of tiles, generated one tile at a time.
+
+use crate::{
+    bitset::BitSet,
+    propagation::{self, Adjacency},
+    rules::RuleSet,
+    tile::Tile,
+    topology::{Line, Topology},
+    wave::Direction,
+};
+
+use rand::Rng;
+
+/// Represents an unbounded sequence of tiles, where each tile is placed
+/// [`Direction::Right`] of the previous one, following the rules and the weights
+/// of the [`Wave`](crate::wave::Wave) it has been created from.
+///
+/// Only variants with a positive weight that can be followed by an unbounded sequence of
+/// such variants are ever chosen, so the sequence never reaches a dead end once started:
+/// it ends right away only if there are no such variants at all, or if the tile it
+/// continues can't be followed by any of them.
+#[derive(Debug, Clone)]
+pub struct Stream<T: Tile, R: Rng> {
+    variants: Vec<T>,
+    adjacency: Adjacency,
+    weights: Vec<f64>,
+    extendable: BitSet,
+    previous: Option<usize>,
+    rng: R,
+}
+
+impl<T: Tile, R: Rng> Stream<T, R> {
+    /// Returns a new sequence following the given rules and weights (indexed by
+    /// variant), whose first tile is placed right of `previous`, if any.
+    pub(crate) fn new(
+        rules: &RuleSet<T>,
+        variants: Vec<T>,
+        weights: Vec<f64>,
+        previous: Option<T>,
+        rng: R,
+    ) -> Self {
+        let adjacency = Adjacency::new(rules, &variants, &Line::new(1));
+
+        let mut extendable = BitSet::empty(variants.len());
+
+        // variants with weight `0.0` are never chosen, so they can't extend the sequence
+        weights
+            .iter()
+            .enumerate()
+            .filter(|(_, weight)| **weight > 0.0)
+            .for_each(|(variant, _)| extendable.insert(variant));
+
+        // keep removing the variants that can't be followed by any extendable variant
+        loop {
+            let dead_end = extendable.iter().find(|variant| {
+                let mut next =
+                    adjacency.allowed(right(), &BitSet::single(variants.len(), *variant));
+
+                let _ = next.intersect_with(&extendable);
+
+                next.is_empty()
+            });
+
+            match dead_end {
+                Some(variant) => extendable.remove(variant),
+                None => break,
+            }
+        }
+
+        Self {
+            previous: previous.and_then(|tile| variants.iter().position(|v| *v == tile)),
+            variants,
+            adjacency,
+            weights,
+            extendable,
+            rng,
+        }
+    }
+}
+
+impl<T: Tile, R: Rng> Iterator for Stream<T, R> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut candidates = match self.previous {
+            Some(previous) => self
+                .adjacency
+                .allowed(right(), &BitSet::single(self.variants.len(), previous)),
+            None => BitSet::full(self.variants.len()),
+        };
+
+        let _ = candidates.intersect_with(&self.extendable);
+
+        let variant = propagation::choose_variant(&candidates, &self.weights, &mut self.rng)?;
+
+        self.previous = Some(variant);
+
+        Some(self.variants[variant])
+    }
+}
+
+/// Returns the index of [`Direction::Right`] among the directions of a [`Line`].
+fn right() -> usize {
+    Line::new(1)
+        .directions()
+        .iter()
+        .position(|d| *d == Direction::Right)
+        .unwrap()
+}
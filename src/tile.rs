@@ -1,68 +1,178 @@
 use std::{fmt, hash::Hash};
 
-use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
+pub use wfc_derive::Tile;
+
 /// Represents a wave tile.
-pub trait Tile: PartialEq + Eq + Clone + Copy + IntoEnumIterator + Hash {}
+///
+/// Tile sets known at compile time are usually enums that also derive
+/// [`EnumIter`], so that their variants can be enumerated automatically.
+///
+/// Every method has a default implementation, and the ones overridden describe the tile
+/// to the rest of the crate: waves use the weight, rule sets use the sockets, and rule
+/// files and renderers use the name and the glyph; the [`Tile`](derive@Tile) derive
+/// macro overrides them through the `#[tile(...)]` attributes of the variants.
+///
+/// # Examples
+///
+/// ```
+/// # use wfc::{tile::Tile, wave::Direction};
+/// #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// pub enum Terrain {
+///     Grass,
+///     Water,
+/// }
+///
+/// impl Tile for Terrain {
+///     fn weight(&self) -> f64 {
+///         match *self {
+///             Self::Grass => 3.0,
+///             Self::Water => 1.0,
+///         }
+///     }
+/// }
+///
+/// assert_eq!(Terrain::Grass.weight(), 3.0);
+/// assert_eq!(Terrain::Water.glyph(), None);
+/// ```
+pub trait Tile: PartialEq + Eq + Clone + Copy + Hash {
+    /// Returns the weight of the tile, used by waves unless
+    /// they set a different one; it's `1.0` by default.
+    fn weight(&self) -> f64 {
+        1.0
+    }
+
+    /// Returns the glyph used to render the tile, if any.
+    fn glyph(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Returns the name used for the tile inside rule files, if any.
+    fn name(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Returns the sockets of the tile, if any, in the format
+    /// read by [`RuleSet::from_sockets`](crate::rules::RuleSet::from_sockets).
+    fn sockets(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Returns the tiles obtained by mirroring the tile, which is symmetric by default.
+    fn symmetry(&self) -> Symmetry<Self> {
+        Symmetry {
+            horizontal: *self,
+            vertical: *self,
+        }
+    }
+}
 
 /// Represents the tiles of a Maze.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, Hash)]
+///
+/// # Examples
+///
+/// The sockets of the tiles describe the rules of `rules/maze.toml`, together with
+/// six rules whose mirrored counterpart is in the file, but which are missing from it:
+///
+/// ```
+/// # use wfc::{format::RuleFile, rules::RuleSet, tile::MazeTile};
+/// let file = RuleFile::<MazeTile>::from_toml(include_str!("../rules/maze.toml")).unwrap();
+/// let rules = RuleSet::<MazeTile>::from_tiles();
+///
+/// assert!(file.rules.difference(&rules).is_empty());
+/// assert_eq!(rules.difference(&file.rules).len(), 6);
+/// assert_eq!(MazeTile::TShaped.to_string(), "┳");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, Hash, Tile)]
+#[tile(display)]
 pub enum MazeTile {
     /// Represents `┳`
+    #[tile(glyph = "┳", sockets = "0111", symmetry(vertical = TShapedUpsideDown))]
     TShaped,
 
     /// Represents `┫`
+    #[tile(glyph = "┫", sockets = "1110", symmetry(horizontal = TShapedRight))]
     TShapedLeft,
 
     /// Represents `┣`
+    #[tile(glyph = "┣", sockets = "1101", symmetry(horizontal = TShapedLeft))]
     TShapedRight,
 
     /// Represents `┻`
+    #[tile(glyph = "┻", sockets = "1011", symmetry(vertical = TShaped))]
     TShapedUpsideDown,
 
     /// Represents `┓`
+    #[tile(glyph = "┓", sockets = "0110", symmetry(horizontal = TopLeftCorner, vertical = BottomRightCorner))]
     TopRightCorner,
 
     /// Represents `┏`
+    #[tile(glyph = "┏", sockets = "0101", symmetry(horizontal = TopRightCorner, vertical = BottomLeftCorner))]
     TopLeftCorner,
 
     /// Represents `┗`
+    #[tile(glyph = "┗", sockets = "1001", symmetry(horizontal = BottomRightCorner, vertical = TopLeftCorner))]
     BottomLeftCorner,
 
     /// Represents `┛`
+    #[tile(glyph = "┛", sockets = "1010", symmetry(horizontal = BottomLeftCorner, vertical = TopRightCorner))]
     BottomRightCorner,
 
     /// Represents `━`
+    #[tile(glyph = "━", sockets = "0011")]
     HorizontalLine,
 
     /// Represents `┃`
+    #[tile(glyph = "┃", sockets = "1100")]
     VerticalLine,
 
     /// Represents `╋`
+    #[tile(glyph = "╋", sockets = "1111")]
     CenterCross,
 
     /// Represents ` `
+    #[tile(glyph = " ", sockets = "0000")]
     Empty,
 }
 
-impl Tile for MazeTile {}
-
-impl fmt::Display for MazeTile {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match *self {
-            Self::TShaped => write!(f, "┳"),
-            Self::TShapedLeft => write!(f, "┫"),
-            Self::TShapedRight => write!(f, "┣"),
-            Self::TShapedUpsideDown => write!(f, "┻"),
-            Self::TopRightCorner => write!(f, "┓"),
-            Self::TopLeftCorner => write!(f, "┏"),
-            Self::BottomLeftCorner => write!(f, "┗"),
-            Self::BottomRightCorner => write!(f, "┛"),
-            Self::HorizontalLine => write!(f, "━"),
-            Self::VerticalLine => write!(f, "┃"),
-            Self::CenterCross => write!(f, "╋"),
-            Self::Empty => write!(f, " "),
+/// Represents the tiles obtained by mirroring a tile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Symmetry<T> {
+    /// The tile mirrored across the vertical axis, swapping its left and right sides.
+    pub horizontal: T,
+
+    /// The tile mirrored across the horizontal axis, swapping its upper and lower sides.
+    pub vertical: T,
+}
+
+/// Represents a tile stacked above another one, in the same cell of a layered wave, where
+/// the first tile belongs to the lower layer; more layers are stacked by nesting pairs.
+///
+/// The weight of a stack is the product of the weights of its tiles, and it's
+/// mirrored by mirroring each of its tiles.
+impl<A: Tile, B: Tile> Tile for (A, B) {
+    fn weight(&self) -> f64 {
+        self.0.weight() * self.1.weight()
+    }
+
+    fn symmetry(&self) -> Symmetry<Self> {
+        let (first, second) = (self.0.symmetry(), self.1.symmetry());
+
+        Symmetry {
+            horizontal: (first.horizontal, second.horizontal),
+            vertical: (first.vertical, second.vertical),
         }
     }
 }
+
+/// Returns the glyph of the given tile, falling back to its [`Display`](fmt::Display) implementation.
+pub(crate) fn glyph<T: Tile + fmt::Display>(tile: &T) -> String {
+    tile.glyph().map_or_else(|| tile.to_string(), String::from)
+}
+
+/// Returns the name of the given tile, falling back to its [`Debug`](fmt::Debug) implementation.
+pub(crate) fn name<T: Tile + fmt::Debug>(tile: &T) -> String {
+    tile.name()
+        .map_or_else(|| format!("{:?}", tile), String::from)
+}
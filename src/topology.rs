@@ -0,0 +1,742 @@
+//! Topologies, which describe the cells of a wave and which cells are
+//! next to each other, in which [`Direction`].
+
+use crate::wave::{Direction, DIRECTIONS_ORDER};
+
+use std::{collections::HashSet, fmt};
+
+use strum::IntoEnumIterator;
+
+/// Represents the directions of the neighbours of a cell inside a [`Line`] topology.
+const LINE_DIRECTIONS: [Direction; 2] = [Direction::Left, Direction::Right];
+
+/// Represents the directions of the neighbours of a cell inside a [`Moore`] topology.
+const MOORE_DIRECTIONS: [Direction; 8] = [
+    Direction::Up,
+    Direction::Down,
+    Direction::Left,
+    Direction::Right,
+    Direction::UpLeft,
+    Direction::UpRight,
+    Direction::DownLeft,
+    Direction::DownRight,
+];
+
+/// Represents the directions of the neighbours of a cell inside a [`Hex`]
+/// topology, clockwise starting from the upper right neighbour.
+const HEX_DIRECTIONS: [Direction; 6] = [
+    Direction::NorthEast,
+    Direction::East,
+    Direction::SouthEast,
+    Direction::SouthWest,
+    Direction::West,
+    Direction::NorthWest,
+];
+
+/// Represents the directions of the neighbours of a cell inside a [`Voxel`] topology.
+const VOXEL_DIRECTIONS: [Direction; 6] = [
+    Direction::Up,
+    Direction::Down,
+    Direction::North,
+    Direction::South,
+    Direction::East,
+    Direction::West,
+];
+
+/// Represents the shape of a wave, where cells are identified by an index in
+/// `0..cells_total()`, and each cell can have a neighbour in each direction.
+pub trait Topology: fmt::Debug + Clone + PartialEq {
+    /// Represents the coordinates of a cell.
+    type Coords: fmt::Debug + Clone + Copy + PartialEq;
+
+    /// Returns the number of cells.
+    fn cells_total(&self) -> usize;
+
+    /// Returns the index of the cell in the given coordinates, if any.
+    fn cell(&self, coords: Self::Coords) -> Option<usize>;
+
+    /// Returns the coordinates of the given cell.
+    fn coords(&self, cell: usize) -> Self::Coords;
+
+    /// Returns the directions in which a cell can have neighbours, which
+    /// are the only directions of the rules that are taken into account.
+    fn directions(&self) -> &[Direction];
+
+    /// Returns the neighbour of the given cell in the given direction, if any.
+    fn neighbour(&self, cell: usize, direction: Direction) -> Option<usize>;
+
+    /// Returns the direction in which a cell is found from its neighbour
+    /// in the given direction, which is [`Direction::opposite`] by default.
+    fn opposite(&self, direction: Direction) -> Direction {
+        direction.opposite()
+    }
+}
+
+/// Represents a topology whose cells are laid out inside a rectangular [`Square`]
+/// grid, with coordinates `(x, y)`, where `y` grows downwards: cells of the grid
+/// which aren't part of the topology are holes.
+pub trait Grid: Topology<Coords = (usize, usize)> {
+    /// Returns the grid the cells are laid out inside of.
+    fn grid(&self) -> Square;
+
+    /// Returns the width of the grid.
+    fn width(&self) -> usize {
+        self.grid().width
+    }
+
+    /// Returns the height of the grid.
+    fn height(&self) -> usize {
+        self.grid().height
+    }
+
+    /// Returns `true` if and only if odd rows are shifted right by half a cell,
+    /// as in [`Hex`] maps, which is `false` by default.
+    fn is_staggered(&self) -> bool {
+        false
+    }
+}
+
+/// Represents a rectangular grid of square cells, with coordinates `(x, y)`,
+/// where each cell has a neighbour [`Direction::Up`], [`Direction::Down`],
+/// [`Direction::Left`] and [`Direction::Right`] of it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Square {
+    width: usize,
+    height: usize,
+}
+
+impl Square {
+    /// Returns a new grid with the given dimensions.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self { width, height }
+    }
+}
+
+impl Grid for Square {
+    fn grid(&self) -> Square {
+        *self
+    }
+}
+
+impl Topology for Square {
+    type Coords = (usize, usize);
+
+    fn cells_total(&self) -> usize {
+        self.width * self.height
+    }
+
+    fn cell(&self, (x, y): Self::Coords) -> Option<usize> {
+        (x < self.width && y < self.height).then_some(y * self.width + x)
+    }
+
+    fn coords(&self, cell: usize) -> Self::Coords {
+        (cell % self.width, cell / self.width)
+    }
+
+    fn directions(&self) -> &[Direction] {
+        &DIRECTIONS_ORDER
+    }
+
+    fn neighbour(&self, cell: usize, direction: Direction) -> Option<usize> {
+        let (x, y) = self.coords(cell);
+
+        let (x, y) = match direction {
+            Direction::Up => (Some(x), y.checked_sub(1)),
+            Direction::Down => (Some(x), Some(y + 1)),
+            Direction::Left => (x.checked_sub(1), Some(y)),
+            Direction::Right => (Some(x + 1), Some(y)),
+            _ => return None,
+        };
+
+        self.cell((x?, y?))
+    }
+}
+
+/// Represents a rectangle of cells inside a grid, whose upper left cell is in `(x, y)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rect {
+    /// The column of the upper left cell.
+    pub x: usize,
+
+    /// The row of the upper left cell.
+    pub y: usize,
+
+    /// The number of columns.
+    pub width: usize,
+
+    /// The number of rows.
+    pub height: usize,
+}
+
+impl Rect {
+    /// Returns a new rectangle with the given upper left cell and dimensions.
+    pub fn new(x: usize, y: usize, width: usize, height: usize) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Returns `true` if and only if the given coordinates are inside the rectangle.
+    pub fn contains(&self, (x, y): (usize, usize)) -> bool {
+        (self.x..self.x + self.width).contains(&x) && (self.y..self.y + self.height).contains(&y)
+    }
+}
+
+/// Represents a rectangular grid of square cells, with coordinates `(x, y)`, where
+/// each cell has eight neighbours (its Moore neighbourhood): the ones of a [`Square`]
+/// grid, and the diagonal ones [`Direction::UpLeft`], [`Direction::UpRight`],
+/// [`Direction::DownLeft`] and [`Direction::DownRight`] of it.
+///
+/// # Examples
+///
+/// ```
+/// # use wfc::{topology::{Moore, Topology}, wave::Direction};
+/// let grid = Moore::new(3, 3);
+///
+/// let center = grid.cell((1, 1)).unwrap();
+///
+/// assert_eq!(grid.neighbour(center, Direction::UpLeft), grid.cell((0, 0)));
+/// assert_eq!(grid.neighbour(center, Direction::DownLeft), grid.cell((0, 2)));
+/// assert_eq!(grid.directions().len(), 8);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Moore {
+    grid: Square,
+}
+
+impl Moore {
+    /// Returns a new grid with the given dimensions.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            grid: Square::new(width, height),
+        }
+    }
+}
+
+impl Topology for Moore {
+    type Coords = (usize, usize);
+
+    fn cells_total(&self) -> usize {
+        self.grid.cells_total()
+    }
+
+    fn cell(&self, coords: Self::Coords) -> Option<usize> {
+        self.grid.cell(coords)
+    }
+
+    fn coords(&self, cell: usize) -> Self::Coords {
+        self.grid.coords(cell)
+    }
+
+    fn directions(&self) -> &[Direction] {
+        &MOORE_DIRECTIONS
+    }
+
+    fn neighbour(&self, cell: usize, direction: Direction) -> Option<usize> {
+        let (x, y) = self.coords(cell);
+
+        let (x, y) = match direction {
+            Direction::Up => (Some(x), y.checked_sub(1)),
+            Direction::Down => (Some(x), Some(y + 1)),
+            Direction::Left => (x.checked_sub(1), Some(y)),
+            Direction::Right => (Some(x + 1), Some(y)),
+            Direction::UpLeft => (x.checked_sub(1), y.checked_sub(1)),
+            Direction::UpRight => (Some(x + 1), y.checked_sub(1)),
+            Direction::DownLeft => (x.checked_sub(1), Some(y + 1)),
+            Direction::DownRight => (Some(x + 1), Some(y + 1)),
+            _ => return None,
+        };
+
+        self.cell((x?, y?))
+    }
+}
+
+impl Grid for Moore {
+    fn grid(&self) -> Square {
+        self.grid
+    }
+}
+
+/// Represents a rectangular grid of square cells, with coordinates `(x, y)`, where opposite
+/// edges are connected to each other, such that cells on the left edge have a neighbour
+/// [`Direction::Left`] of them on the right edge, and the same holds for the top and bottom
+/// edges: this allows to generate textures that tile seamlessly.
+///
+/// # Examples
+///
+/// ```
+/// # use wfc::{topology::{Topology, WrappingSquare}, wave::Direction};
+/// let grid = WrappingSquare::new(3, 2);
+///
+/// let corner = grid.cell((0, 0)).unwrap();
+///
+/// assert_eq!(grid.neighbour(corner, Direction::Left), grid.cell((2, 0)));
+/// assert_eq!(grid.neighbour(corner, Direction::Up), grid.cell((0, 1)));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WrappingSquare {
+    grid: Square,
+}
+
+impl WrappingSquare {
+    /// Returns a new grid with the given dimensions.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            grid: Square::new(width, height),
+        }
+    }
+}
+
+impl Topology for WrappingSquare {
+    type Coords = (usize, usize);
+
+    fn cells_total(&self) -> usize {
+        self.grid.cells_total()
+    }
+
+    fn cell(&self, coords: Self::Coords) -> Option<usize> {
+        self.grid.cell(coords)
+    }
+
+    fn coords(&self, cell: usize) -> Self::Coords {
+        self.grid.coords(cell)
+    }
+
+    fn directions(&self) -> &[Direction] {
+        &DIRECTIONS_ORDER
+    }
+
+    fn neighbour(&self, cell: usize, direction: Direction) -> Option<usize> {
+        let (x, y) = self.coords(cell);
+
+        let (x, y) = match direction {
+            Direction::Up => (x, (y + self.height() - 1) % self.height()),
+            Direction::Down => (x, (y + 1) % self.height()),
+            Direction::Left => ((x + self.width() - 1) % self.width(), y),
+            Direction::Right => ((x + 1) % self.width(), y),
+            _ => return None,
+        };
+
+        self.cell((x, y))
+    }
+}
+
+impl Grid for WrappingSquare {
+    fn grid(&self) -> Square {
+        self.grid
+    }
+}
+
+/// Represents a rectangular map of pointy-topped hexagonal cells, in offset coordinates
+/// `(x, y)` where odd rows are shifted right by half a cell, and each cell has a neighbour
+/// [`Direction::NorthEast`], [`Direction::East`], [`Direction::SouthEast`],
+/// [`Direction::SouthWest`], [`Direction::West`] and [`Direction::NorthWest`] of it.
+///
+/// # Examples
+///
+/// ```
+/// # use wfc::{topology::{Hex, Topology}, wave::Direction};
+/// let hex = Hex::new(4, 4);
+///
+/// let even = hex.cell((1, 2)).unwrap();
+/// let odd = hex.cell((1, 1)).unwrap();
+///
+/// assert_eq!(hex.neighbour(even, Direction::NorthEast), hex.cell((1, 1)));
+/// assert_eq!(hex.neighbour(odd, Direction::NorthEast), hex.cell((2, 0)));
+/// assert_eq!(hex.neighbour(odd, Direction::West), hex.cell((0, 1)));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Hex {
+    grid: Square,
+}
+
+impl Hex {
+    /// Returns a new map with the given dimensions.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            grid: Square::new(width, height),
+        }
+    }
+}
+
+impl Topology for Hex {
+    type Coords = (usize, usize);
+
+    fn cells_total(&self) -> usize {
+        self.grid.cells_total()
+    }
+
+    fn cell(&self, coords: Self::Coords) -> Option<usize> {
+        self.grid.cell(coords)
+    }
+
+    fn coords(&self, cell: usize) -> Self::Coords {
+        self.grid.coords(cell)
+    }
+
+    fn directions(&self) -> &[Direction] {
+        &HEX_DIRECTIONS
+    }
+
+    fn neighbour(&self, cell: usize, direction: Direction) -> Option<usize> {
+        let (x, y) = self.coords(cell);
+
+        // the diagonal neighbours of odd rows are shifted right by one
+        let (west, east) = if y % 2 == 0 {
+            (x.checked_sub(1), Some(x))
+        } else {
+            (Some(x), Some(x + 1))
+        };
+
+        let (x, y) = match direction {
+            Direction::East => (Some(x + 1), Some(y)),
+            Direction::West => (x.checked_sub(1), Some(y)),
+            Direction::NorthEast => (east, y.checked_sub(1)),
+            Direction::NorthWest => (west, y.checked_sub(1)),
+            Direction::SouthEast => (east, Some(y + 1)),
+            Direction::SouthWest => (west, Some(y + 1)),
+            _ => return None,
+        };
+
+        self.cell((x?, y?))
+    }
+}
+
+impl Grid for Hex {
+    fn grid(&self) -> Square {
+        self.grid
+    }
+
+    fn is_staggered(&self) -> bool {
+        true
+    }
+}
+
+/// Represents a box of voxels, with coordinates `(x, y, z)`, where `y` is the vertical axis:
+/// each voxel has a neighbour [`Direction::Up`] (towards greater `y`), [`Direction::Down`],
+/// [`Direction::North`] (towards smaller `z`), [`Direction::South`], [`Direction::East`]
+/// (towards greater `x`) and [`Direction::West`] of it.
+///
+/// # Examples
+///
+/// ```
+/// # use wfc::{topology::{Topology, Voxel}, wave::Direction};
+/// let voxel = Voxel::new(2, 3, 4);
+///
+/// let cell = voxel.cell((1, 0, 2)).unwrap();
+///
+/// assert_eq!(voxel.neighbour(cell, Direction::Up), voxel.cell((1, 1, 2)));
+/// assert_eq!(voxel.neighbour(cell, Direction::North), voxel.cell((1, 0, 1)));
+/// assert_eq!(voxel.neighbour(cell, Direction::East), None);
+/// assert_eq!(voxel.neighbour(cell, Direction::Down), None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Voxel {
+    width: usize,
+    height: usize,
+    depth: usize,
+}
+
+impl Voxel {
+    /// Returns a new box with the given dimensions, along `x`, `y` and `z` respectively.
+    pub fn new(width: usize, height: usize, depth: usize) -> Self {
+        Self {
+            width,
+            height,
+            depth,
+        }
+    }
+
+    /// Returns the width of the box, along `x`.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of the box, along `y`.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the depth of the box, along `z`.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+}
+
+impl Topology for Voxel {
+    type Coords = (usize, usize, usize);
+
+    fn cells_total(&self) -> usize {
+        self.width * self.height * self.depth
+    }
+
+    fn cell(&self, (x, y, z): Self::Coords) -> Option<usize> {
+        (x < self.width && y < self.height && z < self.depth)
+            .then_some((y * self.depth + z) * self.width + x)
+    }
+
+    fn coords(&self, cell: usize) -> Self::Coords {
+        let (layer, x) = (cell / self.width, cell % self.width);
+
+        (x, layer / self.depth, layer % self.depth)
+    }
+
+    fn directions(&self) -> &[Direction] {
+        &VOXEL_DIRECTIONS
+    }
+
+    fn neighbour(&self, cell: usize, direction: Direction) -> Option<usize> {
+        let (x, y, z) = self.coords(cell);
+
+        let (x, y, z) = match direction {
+            Direction::Up => (Some(x), Some(y + 1), Some(z)),
+            Direction::Down => (Some(x), y.checked_sub(1), Some(z)),
+            Direction::North => (Some(x), Some(y), z.checked_sub(1)),
+            Direction::South => (Some(x), Some(y), Some(z + 1)),
+            Direction::East => (Some(x + 1), Some(y), Some(z)),
+            Direction::West => (x.checked_sub(1), Some(y), Some(z)),
+            _ => return None,
+        };
+
+        self.cell((x?, y?, z?))
+    }
+}
+
+/// Represents a sequence of cells, identified by their position, where each
+/// cell has a neighbour [`Direction::Left`] and [`Direction::Right`] of it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Line {
+    length: usize,
+}
+
+impl Line {
+    /// Returns a new sequence with the given length.
+    pub fn new(length: usize) -> Self {
+        Self { length }
+    }
+
+    /// Returns the length of the sequence.
+    pub fn length(&self) -> usize {
+        self.length
+    }
+}
+
+impl Topology for Line {
+    type Coords = usize;
+
+    fn cells_total(&self) -> usize {
+        self.length
+    }
+
+    fn cell(&self, position: Self::Coords) -> Option<usize> {
+        (position < self.length).then_some(position)
+    }
+
+    fn coords(&self, cell: usize) -> Self::Coords {
+        cell
+    }
+
+    fn directions(&self) -> &[Direction] {
+        &LINE_DIRECTIONS
+    }
+
+    fn neighbour(&self, cell: usize, direction: Direction) -> Option<usize> {
+        match direction {
+            Direction::Left => cell.checked_sub(1),
+            Direction::Right => self.cell(cell + 1),
+            _ => None,
+        }
+    }
+}
+
+/// Represents a grid of square cells of any shape, such as a circular arena or an
+/// L-shaped building, with coordinates `(x, y)` inside a rectangle: only the cells
+/// inside the shape are part of it, and the ones outside of it are holes, which are
+/// never neighbours of any cell, as if they were out of bounds.
+///
+/// # Examples
+///
+/// ```
+/// # use wfc::{topology::{Shape, Topology}, wave::Direction};
+/// // an L-shaped building
+/// let shape = Shape::new(4, 4, |(x, y)| x < 2 || y >= 2);
+///
+/// let cell = shape.cell((1, 1)).unwrap();
+///
+/// assert_eq!(shape.cells_total(), 12);
+/// assert_eq!(shape.cell((3, 0)), None);
+/// assert_eq!(shape.neighbour(cell, Direction::Down), shape.cell((1, 2)));
+/// assert_eq!(shape.neighbour(cell, Direction::Right), None);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Shape {
+    grid: Square,
+    cells: Vec<Option<usize>>,
+    coords: Vec<(usize, usize)>,
+}
+
+impl Shape {
+    /// Returns a new grid with the given dimensions, whose shape
+    /// is made of the cells for which `inside` returns `true`.
+    pub fn new<F: Fn((usize, usize)) -> bool>(width: usize, height: usize, inside: F) -> Self {
+        let grid = Square::new(width, height);
+
+        let coords = (0..grid.cells_total())
+            .map(|cell| grid.coords(cell))
+            .filter(|coords| inside(*coords))
+            .collect::<Vec<_>>();
+
+        let mut cells = vec![None; grid.cells_total()];
+
+        coords
+            .iter()
+            .enumerate()
+            .for_each(|(cell, (x, y))| cells[y * width + x] = Some(cell));
+
+        Self {
+            grid,
+            cells,
+            coords,
+        }
+    }
+
+    /// Returns `true` if and only if the given coordinates are inside the shape.
+    pub fn contains(&self, coords: (usize, usize)) -> bool {
+        self.cell(coords).is_some()
+    }
+}
+
+impl Topology for Shape {
+    type Coords = (usize, usize);
+
+    fn cells_total(&self) -> usize {
+        self.coords.len()
+    }
+
+    fn cell(&self, coords: Self::Coords) -> Option<usize> {
+        self.cells[self.grid.cell(coords)?]
+    }
+
+    fn coords(&self, cell: usize) -> Self::Coords {
+        self.coords[cell]
+    }
+
+    fn directions(&self) -> &[Direction] {
+        &DIRECTIONS_ORDER
+    }
+
+    fn neighbour(&self, cell: usize, direction: Direction) -> Option<usize> {
+        let other = self
+            .grid
+            .neighbour(self.grid.cell(self.coords(cell))?, direction)?;
+
+        self.cells[other]
+    }
+}
+
+impl Grid for Shape {
+    fn grid(&self) -> Square {
+        self.grid
+    }
+}
+
+/// Represents an arbitrary graph of cells, such as the rooms of a dungeon or the regions
+/// of a Voronoi diagram, where cells are identified by their index and each cell has at
+/// most one neighbour in each direction, connected through [`Graph::connect`].
+///
+/// # Examples
+///
+/// ```
+/// # use wfc::{topology::{Graph, Topology}, wave::Direction};
+/// let mut graph = Graph::new(3);
+///
+/// graph.connect(0, 1, Direction::East);
+/// graph.connect(1, 2, Direction::North);
+///
+/// assert_eq!(graph.neighbour(1, Direction::West), Some(0));
+/// assert_eq!(graph.neighbour(2, Direction::South), Some(1));
+/// assert_eq!(graph.neighbour(0, Direction::North), None);
+/// assert_eq!(graph.directions(), [Direction::North, Direction::South, Direction::East, Direction::West]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Graph {
+    neighbours: Vec<Vec<(Direction, usize)>>,
+    directions: Vec<Direction>,
+}
+
+impl Graph {
+    /// Returns a new graph with the given number of cells, and no connections.
+    pub fn new(cells_total: usize) -> Self {
+        Self {
+            neighbours: vec![Vec::new(); cells_total],
+            directions: Vec::new(),
+        }
+    }
+
+    /// Connects `second_cell` in the `direction` direction of `first_cell`, and `first_cell`
+    /// in the opposite direction of `second_cell`, replacing any previous neighbour.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the cells doesn't belong to the graph.
+    pub fn connect(&mut self, first_cell: usize, second_cell: usize, direction: Direction) {
+        assert!(
+            first_cell < self.neighbours.len() && second_cell < self.neighbours.len(),
+            "cell out of bounds"
+        );
+
+        self.link(first_cell, second_cell, direction);
+        self.link(second_cell, first_cell, direction.opposite());
+
+        let used = self
+            .neighbours
+            .iter()
+            .flatten()
+            .map(|(direction, _)| *direction)
+            .collect::<HashSet<_>>();
+
+        self.directions = Direction::iter().filter(|d| used.contains(d)).collect();
+    }
+
+    /// Sets `second_cell` as the neighbour in the `direction` direction of `first_cell`,
+    /// disconnecting the previous neighbour in that direction, if any.
+    fn link(&mut self, first_cell: usize, second_cell: usize, direction: Direction) {
+        if let Some(previous) = self.neighbour(first_cell, direction) {
+            self.neighbours[previous]
+                .retain(|(d, cell)| *d != direction.opposite() || *cell != first_cell);
+        }
+
+        self.neighbours[first_cell].retain(|(d, _)| *d != direction);
+        self.neighbours[first_cell].push((direction, second_cell));
+    }
+}
+
+impl Topology for Graph {
+    type Coords = usize;
+
+    fn cells_total(&self) -> usize {
+        self.neighbours.len()
+    }
+
+    fn cell(&self, cell: Self::Coords) -> Option<usize> {
+        (cell < self.neighbours.len()).then_some(cell)
+    }
+
+    fn coords(&self, cell: usize) -> Self::Coords {
+        cell
+    }
+
+    fn directions(&self) -> &[Direction] {
+        &self.directions
+    }
+
+    fn neighbour(&self, cell: usize, direction: Direction) -> Option<usize> {
+        self.neighbours[cell]
+            .iter()
+            .find(|(d, _)| *d == direction)
+            .map(|(_, cell)| *cell)
+    }
+}
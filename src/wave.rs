@@ -1,45 +1,156 @@
-use crate::tile::Tile;
+use crate::{
+    bitset::BitSet,
+    constraint::{Constraint, Constraints, Domains},
+    propagation::{self, Adjacency, Neighbours},
+    rules::RuleSet,
+    sequence::Stream,
+    tile::{self, Tile},
+    topology::{
+        Graph, Grid, Hex, Line, Moore, Rect, Shape, Square, Topology, Voxel, WrappingSquare,
+    },
+};
 
-use std::{collections::HashSet, fmt, hash::Hash};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{self, Write},
+    hash::Hash,
+    str::FromStr,
+    sync::Arc,
+};
 
-use rand::{seq::IteratorRandom, Rng};
+use rand::Rng;
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
 
 /// Represents the order of which
 /// the neighbours of a tile are going to be visited.
-const DIRECTIONS_ORDER: [Direction; 4] = [
+pub(crate) const DIRECTIONS_ORDER: [Direction; 4] = [
     Direction::Up,
     Direction::Down,
     Direction::Left,
     Direction::Right,
 ];
 
-/// Represents a direction.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// Represents the directions of the diagonal neighbours of a tile.
+pub(crate) const DIAGONAL_DIRECTIONS: [Direction; 4] = [
+    Direction::UpLeft,
+    Direction::UpRight,
+    Direction::DownLeft,
+    Direction::DownRight,
+];
+
+/// Represents a direction: each [`Topology`] only uses some of them, such as `Up`,
+/// `Down`, `Left` and `Right` for grids of square cells (together with `UpLeft`,
+/// `UpRight`, `DownLeft` and `DownRight` if diagonal neighbours are taken into
+/// account), or `Up`, `Down`, `North`, `South`, `East` and `West` for grids of voxels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, Hash)]
 pub enum Direction {
     Up,
     Down,
     Left,
     Right,
+    North,
+    South,
+    East,
+    West,
+    NorthEast,
+    NorthWest,
+    SouthEast,
+    SouthWest,
+    UpLeft,
+    UpRight,
+    DownLeft,
+    DownRight,
 }
 
-/// Represents a wave state.
-#[derive(Debug, Clone)]
-pub struct Wave<T: Tile + Hash> {
-    width: usize,
-    height: usize,
-    tiles: Vec<Vec<(Option<T>, usize)>>,
-    variants_total: usize,
-    rules: HashSet<(T, T, Direction)>,
+impl Direction {
+    /// Returns the opposite direction.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::wave::Direction;
+    /// assert_eq!(Direction::Up.opposite(), Direction::Down);
+    /// ```
+    pub fn opposite(self) -> Self {
+        match self {
+            Self::Up => Self::Down,
+            Self::Down => Self::Up,
+            Self::Left => Self::Right,
+            Self::Right => Self::Left,
+            Self::North => Self::South,
+            Self::South => Self::North,
+            Self::East => Self::West,
+            Self::West => Self::East,
+            Self::NorthEast => Self::SouthWest,
+            Self::NorthWest => Self::SouthEast,
+            Self::SouthEast => Self::NorthWest,
+            Self::SouthWest => Self::NorthEast,
+            Self::UpLeft => Self::DownRight,
+            Self::UpRight => Self::DownLeft,
+            Self::DownLeft => Self::UpRight,
+            Self::DownRight => Self::UpLeft,
+        }
+    }
 }
 
-impl<T: Tile + Hash> Wave<T> {
-    /// Returns a new wave.
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Up => write!(f, "up"),
+            Self::Down => write!(f, "down"),
+            Self::Left => write!(f, "left"),
+            Self::Right => write!(f, "right"),
+            Self::North => write!(f, "north"),
+            Self::South => write!(f, "south"),
+            Self::East => write!(f, "east"),
+            Self::West => write!(f, "west"),
+            Self::NorthEast => write!(f, "north_east"),
+            Self::NorthWest => write!(f, "north_west"),
+            Self::SouthEast => write!(f, "south_east"),
+            Self::SouthWest => write!(f, "south_west"),
+            Self::UpLeft => write!(f, "up_left"),
+            Self::UpRight => write!(f, "up_right"),
+            Self::DownLeft => write!(f, "down_left"),
+            Self::DownRight => write!(f, "down_right"),
+        }
+    }
+}
+
+impl FromStr for Direction {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::iter()
+            .find(|direction| direction.to_string() == s)
+            .ok_or(())
+    }
+}
+
+/// Represents a wave state, whose cells are laid out following
+/// the given [`Topology`], which is a grid of square cells by default.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Wave<T: Tile + Hash, P: Topology = Square> {
+    topology: P,
+    domains: Vec<BitSet>,
+    allowed: Vec<BitSet>,
+    variants: Vec<T>,
+    rules: Arc<RuleSet<T>>,
+    weights: HashMap<T, f64>,
+    constraints: Constraints<T, P>,
+}
+
+impl<T: Tile + Hash + IntoEnumIterator> Wave<T> {
+    /// Returns a new wave, following the given rules.
+    ///
+    /// The rules can be either an owned [`RuleSet`] or an `Arc<RuleSet>`,
+    /// which allows to share the same rules across many waves without cloning them.
     ///
     /// # Examples
     ///
     /// ```
-    /// # use wfc::wave::Wave;
-    /// # use std::{fmt, collections::HashSet};
+    /// # use wfc::{rules::RuleSet, wave::Wave};
+    /// # use std::{fmt, sync::Arc};
     /// # use strum_macros::EnumIter;
     /// use wfc::tile::Tile;
     ///
@@ -51,38 +162,901 @@ impl<T: Tile + Hash> Wave<T> {
     ///
     /// impl Tile for SimpleTile {}
     ///
-    /// let wave = Wave::<SimpleTile>::new(10, 10, HashSet::new()).unwrap();
+    /// let wave = Wave::<SimpleTile>::new(10, 10, RuleSet::new()).unwrap();
+    ///
+    /// let rules = Arc::new(RuleSet::new());
+    ///
+    /// let first = Wave::<SimpleTile>::new(10, 10, Arc::clone(&rules)).unwrap();
+    /// let second = Wave::<SimpleTile>::new(20, 20, rules).unwrap();
+    /// ```
+    pub fn new<R: Into<Arc<RuleSet<T>>>>(
+        width: usize,
+        height: usize,
+        rules: R,
+    ) -> Result<Self, WaveError> {
+        Self::with_variants(width, height, T::iter().collect(), rules)
+    }
+
+    /// Returns a new wave, following the rules described by the variants
+    /// themselves, as [`RuleSet::from_tiles`] does, without any other configuration.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{tile::MazeTile, wave::Wave};
+    /// # use rand::thread_rng;
+    /// let mut wave = Wave::<MazeTile>::from_tiles(10, 10).unwrap();
+    ///
+    /// wave.collapse(&mut thread_rng()).unwrap();
     /// ```
-    pub fn new(
+    pub fn from_tiles(width: usize, height: usize) -> Result<Self, WaveError> {
+        Self::new(width, height, RuleSet::from_tiles())
+    }
+}
+
+impl<T: Tile + Hash> Wave<T> {
+    /// Returns a new wave, following the given rules, whose tiles can
+    /// collapse to the given (distinct) variants: this allows to use tile
+    /// sets that are only known at runtime, such as [`DynamicTileSet`]s.
+    ///
+    /// Returns [`WaveError::InvalidVariants`] if there are no variants,
+    /// or if any of them is repeated.
+    ///
+    /// [`DynamicTileSet`]: crate::dynamic::DynamicTileSet
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{rules::RuleSet, wave::Wave};
+    /// use wfc::tile::Tile;
+    ///
+    /// #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    /// pub struct Height(u8);
+    ///
+    /// impl Tile for Height {}
+    ///
+    /// let variants = (0..10).map(Height).collect::<Vec<_>>();
+    ///
+    /// let wave = Wave::with_variants(10, 10, variants, RuleSet::new()).unwrap();
+    ///
+    /// assert!(Wave::with_variants(10, 10, vec![Height(1), Height(1)], RuleSet::new()).is_err());
+    /// ```
+    pub fn with_variants<R: Into<Arc<RuleSet<T>>>>(
         width: usize,
         height: usize,
-        rules: HashSet<(T, T, Direction)>,
+        variants: Vec<T>,
+        rules: R,
+    ) -> Result<Self, WaveError> {
+        Self::with_topology(Square::new(width, height), variants, rules)
+    }
+
+    /// Uncollapses the tiles in the given rectangle, as [`Wave::uncollapse`] does,
+    /// so that collapsing the wave again only rerolls them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{rules::RuleSet, tile::MazeTile, topology::Rect, wave::Wave};
+    /// # use rand::thread_rng;
+    /// let mut rng = thread_rng();
+    ///
+    /// let mut wave = Wave::<MazeTile>::from_tiles(10, 10).unwrap();
+    ///
+    /// while wave.collapse(&mut rng).is_err() {
+    ///     wave = Wave::from_tiles(10, 10).unwrap();
+    /// }
+    ///
+    /// let before = wave.clone();
+    ///
+    /// wave.uncollapse_region(Rect::new(2, 2, 4, 4)).unwrap();
+    ///
+    /// assert_eq!(wave.get((1, 1)), before.get((1, 1)));
+    ///
+    /// wave.collapse(&mut rng).unwrap();
+    ///
+    /// assert_eq!(wave.get((7, 3)), before.get((7, 3)));
+    /// assert!(wave.get((3, 3)).is_some());
+    /// ```
+    pub fn uncollapse_region(&mut self, rect: Rect) -> Result<(), WaveError> {
+        self.uncollapse(|coords| rect.contains(coords))
+    }
+
+    /// Returns the tiles in the given rectangle as a new wave, with the same variants, rules
+    /// and weights, where each tile keeps the variants it can still collapse to.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the rectangle isn't inside the wave.
+    pub(crate) fn crop(&self, rect: Rect) -> Self {
+        let topology = Square::new(rect.width, rect.height);
+
+        let cells = (0..topology.cells_total())
+            .map(|cell| {
+                let (x, y) = topology.coords(cell);
+
+                self.topology
+                    .cell((rect.x + x, rect.y + y))
+                    .expect("rectangle out of bounds")
+            })
+            .collect::<Vec<_>>();
+
+        Self {
+            domains: cells
+                .iter()
+                .map(|cell| self.domains[*cell].clone())
+                .collect(),
+            allowed: cells
+                .iter()
+                .map(|cell| self.allowed[*cell].clone())
+                .collect(),
+            topology,
+            variants: self.variants.clone(),
+            rules: Arc::clone(&self.rules),
+            weights: self.weights.clone(),
+            constraints: Constraints::default(),
+        }
+    }
+}
+
+impl<A: Tile + IntoEnumIterator, B: Tile + IntoEnumIterator> Wave<(A, B)> {
+    /// Returns a new wave of two stacked layers, where each cell holds a tile of the
+    /// lower layer and a tile of the upper one: the tiles of each layer follow the rules
+    /// of that layer, as in [`RuleSet::layered`], and the tiles in the same cell can be
+    /// stacked only if `stackable` returns `true` for them.
+    ///
+    /// More layers can be stacked by nesting pairs, through [`Wave::with_variants`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{rules::RuleSet, tile::Tile, wave::{Direction, Wave}};
+    /// # use strum_macros::EnumIter;
+    /// # use rand::thread_rng;
+    /// #[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, Hash, Tile)]
+    /// pub enum Floor {
+    ///     Wall,
+    ///     Ground,
+    /// }
+    ///
+    /// #[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, Hash, Tile)]
+    /// pub enum Decoration {
+    ///     Torch,
+    ///     Nothing,
+    /// }
+    ///
+    /// let floor = RuleSet::new()
+    ///     .allow_both_ways(Floor::Wall, Floor::Ground, Direction::Up)
+    ///     .allow_both_ways(Floor::Wall, Floor::Ground, Direction::Left)
+    ///     .allow_both_ways(Floor::Wall, Floor::Wall, Direction::Up)
+    ///     .allow_both_ways(Floor::Wall, Floor::Wall, Direction::Left)
+    ///     .allow_both_ways(Floor::Ground, Floor::Ground, Direction::Up)
+    ///     .allow_both_ways(Floor::Ground, Floor::Ground, Direction::Left);
+    ///
+    /// // torches are never next to each other
+    /// let decoration = RuleSet::new()
+    ///     .allow_both_ways(Decoration::Torch, Decoration::Nothing, Direction::Up)
+    ///     .allow_both_ways(Decoration::Torch, Decoration::Nothing, Direction::Left)
+    ///     .allow_both_ways(Decoration::Nothing, Decoration::Nothing, Direction::Up)
+    ///     .allow_both_ways(Decoration::Nothing, Decoration::Nothing, Direction::Left);
+    ///
+    /// // torches only hang on walls
+    /// let mut wave = Wave::layered(8, 8, &floor, &decoration, |floor, decoration| {
+    ///     decoration != Decoration::Torch || floor == Floor::Wall
+    /// })
+    /// .unwrap();
+    ///
+    /// wave.collapse(&mut thread_rng()).unwrap();
+    ///
+    /// for y in 0..8 {
+    ///     for x in 0..8 {
+    ///         if let Some((floor, Decoration::Torch)) = wave.get((x, y)) {
+    ///             assert_eq!(floor, Floor::Wall);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn layered<F: Fn(A, B) -> bool>(
+        width: usize,
+        height: usize,
+        first: &RuleSet<A>,
+        second: &RuleSet<B>,
+        stackable: F,
+    ) -> Result<Self, WaveError> {
+        let variants = A::iter()
+            .flat_map(|lower| B::iter().map(move |upper| (lower, upper)))
+            .filter(|(lower, upper)| stackable(*lower, *upper))
+            .collect();
+
+        Self::with_variants(width, height, variants, RuleSet::layered(first, second))
+    }
+}
+
+impl<T: Tile + Hash + IntoEnumIterator> Wave<T, Hex> {
+    /// Returns a new wave laid out as a [`Hex`] map, following the given rules,
+    /// which are expressed in the directions of the hexagonal cells.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{rules::RuleSet, tile::Tile, wave::{Direction, Wave}};
+    /// # use strum_macros::EnumIter;
+    /// # use rand::thread_rng;
+    /// #[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, Hash, Tile)]
+    /// #[tile(display)]
+    /// pub enum Terrain {
+    ///     #[tile(glyph = "~")]
+    ///     Water,
+    ///
+    ///     #[tile(glyph = "^")]
+    ///     Mountain,
+    /// }
+    ///
+    /// // each row has a single terrain, and rows of water are never next to each other
+    /// let rules = [Direction::NorthEast, Direction::NorthWest].into_iter().fold(
+    ///     RuleSet::new()
+    ///         .allow_both_ways(Terrain::Water, Terrain::Water, Direction::East)
+    ///         .allow_both_ways(Terrain::Mountain, Terrain::Mountain, Direction::East),
+    ///     |rules, direction| {
+    ///         rules
+    ///             .allow_both_ways(Terrain::Mountain, Terrain::Mountain, direction)
+    ///             .allow_both_ways(Terrain::Water, Terrain::Mountain, direction)
+    ///             .allow_both_ways(Terrain::Mountain, Terrain::Water, direction)
+    ///     },
+    /// );
+    ///
+    /// let mut wave = Wave::hex(3, 3, rules).unwrap();
+    ///
+    /// wave.collapse(&mut thread_rng()).unwrap();
+    ///
+    /// // odd rows are shifted right by half a cell
+    /// let rendered = wave.to_string();
+    ///
+    /// assert!(["~ ~ ~", "^ ^ ^"].contains(&rendered.lines().nth(1).unwrap().trim()));
+    /// ```
+    pub fn hex<R: Into<Arc<RuleSet<T>>>>(
+        width: usize,
+        height: usize,
+        rules: R,
+    ) -> Result<Self, WaveError> {
+        Self::with_topology(Hex::new(width, height), T::iter().collect(), rules)
+    }
+}
+
+impl<T: Tile + Hash + IntoEnumIterator> Wave<T, WrappingSquare> {
+    /// Returns a new wave laid out as a [`WrappingSquare`] grid, following the given rules,
+    /// whose opposite edges are consistent with each other.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{rules::RuleSet, tile::{MazeTile, Tile}, wave::Wave};
+    /// # use rand::thread_rng;
+    /// let wave = loop {
+    ///     let mut wave = Wave::<MazeTile, _>::wrapping(4, 4, RuleSet::from_tiles()).unwrap();
+    ///
+    ///     if wave.collapse(&mut thread_rng()).is_ok() {
+    ///         break wave;
+    ///     }
+    /// };
+    ///
+    /// // the corridors continue across the edges
+    /// let side = |coords, i| wave.get(coords).unwrap().sockets().unwrap().chars().nth(i);
+    ///
+    /// for i in 0..4 {
+    ///     assert_eq!(side((3, i), 3), side((0, i), 2));
+    ///     assert_eq!(side((i, 3), 1), side((i, 0), 0));
+    /// }
+    /// ```
+    pub fn wrapping<R: Into<Arc<RuleSet<T>>>>(
+        width: usize,
+        height: usize,
+        rules: R,
+    ) -> Result<Self, WaveError> {
+        Self::with_topology(
+            WrappingSquare::new(width, height),
+            T::iter().collect(),
+            rules,
+        )
+    }
+}
+
+impl<T: Tile + Hash + IntoEnumIterator> Wave<T, Graph> {
+    /// Returns a new wave laid out as the given [`Graph`], following the given rules,
+    /// which are expressed in the directions that label the connections of the graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{rules::RuleSet, tile::Tile, topology::Graph, wave::{Direction, Wave}};
+    /// # use strum_macros::EnumIter;
+    /// # use rand::thread_rng;
+    /// #[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, Hash, Tile)]
+    /// pub enum Room {
+    ///     Hall,
+    ///     Treasure,
+    /// }
+    ///
+    /// // a hall with a room on each side
+    /// let mut graph = Graph::new(5);
+    ///
+    /// graph.connect(0, 1, Direction::North);
+    /// graph.connect(0, 2, Direction::South);
+    /// graph.connect(0, 3, Direction::East);
+    /// graph.connect(0, 4, Direction::West);
+    ///
+    /// // treasure rooms can only be reached from halls
+    /// let rules = [Direction::North, Direction::East]
+    ///     .into_iter()
+    ///     .fold(RuleSet::new(), |rules, direction| {
+    ///         rules
+    ///             .allow_both_ways(Room::Hall, Room::Hall, direction)
+    ///             .allow_both_ways(Room::Hall, Room::Treasure, direction)
+    ///     });
+    ///
+    /// let mut wave = Wave::graph(graph, rules).unwrap();
+    ///
+    /// wave.collapse(&mut thread_rng()).unwrap();
+    ///
+    /// assert!((1..5).all(|room| wave.get(0) == Some(Room::Hall) || wave.get(room) == Some(Room::Hall)));
+    /// ```
+    pub fn graph<R: Into<Arc<RuleSet<T>>>>(graph: Graph, rules: R) -> Result<Self, WaveError> {
+        Self::with_topology(graph, T::iter().collect(), rules)
+    }
+}
+
+impl<T: Tile + Hash + IntoEnumIterator> Wave<T, Shape> {
+    /// Returns a new wave laid out as the given [`Shape`], following the given rules,
+    /// where the cells next to the holes of the shape are free, as if they were on the
+    /// edges of the wave, unless the holes are filled through [`Wave::surround_with`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{rules::RuleSet, tile::{MazeTile, Tile}, topology::Shape, wave::Wave};
+    /// # use rand::thread_rng;
+    /// // a circular arena, whose corridors never lead outside of it
+    /// let arena = Shape::new(9, 9, |(x, y)| (x as i32 - 4).pow(2) + (y as i32 - 4).pow(2) <= 16);
+    ///
+    /// let mut wave = Wave::<MazeTile, _>::shaped(arena, RuleSet::from_tiles()).unwrap();
+    ///
+    /// wave.surround_with(MazeTile::Empty).unwrap();
+    ///
+    /// assert!(!wave.topology().contains((0, 0)));
+    /// assert!(!wave.domain((0, 4)).contains(&MazeTile::HorizontalLine));
+    ///
+    /// wave.collapse(&mut thread_rng()).unwrap();
+    ///
+    /// // the holes are rendered as spaces
+    /// assert!(wave.to_string().starts_with("    "));
+    /// assert_eq!(wave.get((0, 4)).unwrap().sockets().unwrap().chars().nth(2), Some('0'));
+    /// ```
+    pub fn shaped<R: Into<Arc<RuleSet<T>>>>(shape: Shape, rules: R) -> Result<Self, WaveError> {
+        Self::with_topology(shape, T::iter().collect(), rules)
+    }
+}
+
+impl<T: Tile + Hash> Wave<T, Shape> {
+    /// Fills the holes of the wave, and the cells out of its bounds, with the given
+    /// virtual tile: each cell next to them can only collapse to the variants that
+    /// the current rules allow next to the virtual tile.
+    ///
+    /// Returns [`WaveError::UncollapsibleWave`], leaving the wave unchanged,
+    /// if any cell can't collapse to any variant.
+    pub fn surround_with(&mut self, tile: T) -> Result<(), WaveError> {
+        let topology = self.topology.clone();
+        let rules = Arc::clone(&self.rules);
+
+        self.restrict_cells(0..topology.cells_total(), |cell, variant| {
+            topology.directions().iter().all(|direction| {
+                topology.neighbour(cell, *direction).is_some()
+                    || (rules.contains((variant, tile, *direction))
+                        && rules.contains((tile, variant, topology.opposite(*direction))))
+            })
+        })
+    }
+}
+
+impl<T: Tile + Hash + IntoEnumIterator> Wave<T, Moore> {
+    /// Returns a new wave laid out as a [`Moore`] grid, following the given
+    /// rules, where diagonal neighbours are constrained by the rules as well.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{rules::RuleSet, tile::Tile, wave::{Direction, Wave}};
+    /// # use strum_macros::EnumIter;
+    /// # use rand::thread_rng;
+    /// #[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, Hash, Tile)]
+    /// #[tile(display)]
+    /// pub enum Cell {
+    ///     #[tile(glyph = "#")]
+    ///     Black,
+    ///
+    ///     #[tile(glyph = ".")]
+    ///     White,
+    /// }
+    ///
+    /// // a checkerboard: equal cells are only allowed diagonally
+    /// let rules = [Direction::Up, Direction::Left]
+    ///     .into_iter()
+    ///     .fold(RuleSet::new(), |rules, direction| {
+    ///         rules
+    ///             .allow_both_ways(Cell::Black, Cell::White, direction)
+    ///             .allow_both_ways(Cell::White, Cell::Black, direction)
+    ///     });
+    ///
+    /// let rules = [Direction::UpLeft, Direction::UpRight]
+    ///     .into_iter()
+    ///     .fold(rules, |rules, direction| {
+    ///         rules
+    ///             .allow_both_ways(Cell::Black, Cell::Black, direction)
+    ///             .allow_both_ways(Cell::White, Cell::White, direction)
+    ///     });
+    ///
+    /// let mut wave = Wave::moore(4, 2, rules).unwrap();
+    ///
+    /// wave.collapse(&mut thread_rng()).unwrap();
+    ///
+    /// assert!(["#.#.\n.#.#\n", ".#.#\n#.#.\n"].contains(&wave.to_string().as_str()));
+    /// ```
+    pub fn moore<R: Into<Arc<RuleSet<T>>>>(
+        width: usize,
+        height: usize,
+        rules: R,
+    ) -> Result<Self, WaveError> {
+        Self::with_topology(Moore::new(width, height), T::iter().collect(), rules)
+    }
+}
+
+impl<T: Tile + Hash, P: Grid> Wave<T, P> {
+    /// Returns the width of the wave.
+    pub fn width(&self) -> usize {
+        self.topology.width()
+    }
+
+    /// Returns the height of the wave.
+    pub fn height(&self) -> usize {
+        self.topology.height()
+    }
+}
+
+impl<T: Tile + Hash + IntoEnumIterator> Wave<T, Voxel> {
+    /// Returns a new wave laid out as a [`Voxel`] box, following the given rules,
+    /// which are expressed in the directions of the voxels.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{rules::RuleSet, tile::Tile, wave::{Direction, Wave}};
+    /// # use strum_macros::EnumIter;
+    /// # use rand::thread_rng;
+    /// #[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, Hash, Tile)]
+    /// pub enum Block {
+    ///     #[tile(glyph = "#")]
+    ///     Wall,
+    ///
+    ///     #[tile(glyph = "=")]
+    ///     Roof,
+    /// }
+    ///
+    /// // roofs are only placed above walls, and walls can be next to each other
+    /// let rules = RuleSet::new()
+    ///     .allow_both_ways(Block::Wall, Block::Roof, Direction::Up)
+    ///     .allow_both_ways(Block::Wall, Block::Wall, Direction::Up)
+    ///     .allow_both_ways(Block::Roof, Block::Roof, Direction::East)
+    ///     .allow_both_ways(Block::Roof, Block::Roof, Direction::North)
+    ///     .allow_both_ways(Block::Wall, Block::Wall, Direction::East)
+    ///     .allow_both_ways(Block::Wall, Block::Wall, Direction::North);
+    ///
+    /// let mut wave = Wave::voxel(2, 3, 2, rules).unwrap();
+    ///
+    /// wave.set_weight(Block::Roof, 0.0); // only walls, this time
+    ///
+    /// wave.collapse(&mut thread_rng()).unwrap();
+    ///
+    /// assert_eq!(wave.get((1, 2, 1)), Some(Block::Wall));
+    /// assert!(wave.to_xyz().starts_with("0 0 0 Wall\n1 0 0 Wall\n"));
+    /// ```
+    pub fn voxel<R: Into<Arc<RuleSet<T>>>>(
+        width: usize,
+        height: usize,
+        depth: usize,
+        rules: R,
+    ) -> Result<Self, WaveError> {
+        Self::with_topology(Voxel::new(width, height, depth), T::iter().collect(), rules)
+    }
+}
+
+impl<T: Tile + Hash> Wave<T, Voxel> {
+    /// Returns the width of the wave, along `x`.
+    pub fn width(&self) -> usize {
+        self.topology.width()
+    }
+
+    /// Returns the height of the wave, along `y`.
+    pub fn height(&self) -> usize {
+        self.topology.height()
+    }
+
+    /// Returns the depth of the wave, along `z`.
+    pub fn depth(&self) -> usize {
+        self.topology.depth()
+    }
+}
+
+impl<T: Tile + Hash + fmt::Debug> Wave<T, Voxel> {
+    /// Returns the collapsed voxels of the wave in the XYZ text format, with a line
+    /// `x y z name` for each voxel, named after its [`Tile::name`] (or its variant name),
+    /// ordered by `y`, then by `z`, then by `x`.
+    pub fn to_xyz(&self) -> String {
+        let mut xyz = String::new();
+
+        for cell in 0..self.topology.cells_total() {
+            let (x, y, z) = self.topology.coords(cell);
+
+            if let Some(v) = self.get((x, y, z)) {
+                let _ = writeln!(xyz, "{} {} {} {}", x, y, z, tile::name(&v));
+            }
+        }
+
+        xyz
+    }
+}
+
+impl<T: Tile + Hash + IntoEnumIterator> Wave<T, Line> {
+    /// Returns a new wave laid out as a [`Line`] of the given length, following the
+    /// given rules, where each tile is placed [`Direction::Right`] of the previous one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{rules::RuleSet, tile::Tile, wave::{Direction, Wave}};
+    /// # use strum_macros::EnumIter;
+    /// # use rand::thread_rng;
+    /// #[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, Hash, Tile)]
+    /// pub enum Room {
+    ///     Hall,
+    ///     Corridor,
+    /// }
+    ///
+    /// // halls are always separated by corridors
+    /// let rules = RuleSet::new()
+    ///     .allow_both_ways(Room::Hall, Room::Corridor, Direction::Right)
+    ///     .allow_both_ways(Room::Corridor, Room::Hall, Direction::Right)
+    ///     .allow_both_ways(Room::Corridor, Room::Corridor, Direction::Right);
+    ///
+    /// let mut wave = Wave::line(12, rules).unwrap();
+    ///
+    /// wave.collapse(&mut thread_rng()).unwrap();
+    ///
+    /// let rooms = wave.to_vec().unwrap();
+    ///
+    /// assert_eq!(rooms.len(), 12);
+    /// assert!(rooms.windows(2).all(|pair| pair != [Room::Hall, Room::Hall]));
+    /// ```
+    pub fn line<R: Into<Arc<RuleSet<T>>>>(length: usize, rules: R) -> Result<Self, WaveError> {
+        Self::with_topology(Line::new(length), T::iter().collect(), rules)
+    }
+}
+
+impl<T: Tile + Hash> Wave<T, Line> {
+    /// Returns the length of the wave.
+    pub fn length(&self) -> usize {
+        self.topology.length()
+    }
+
+    /// Returns the variants of the tiles of the wave, from left to right.
+    ///
+    /// Returns [`WaveError::NotFullyCollapsed`] if any tile hasn't collapsed yet.
+    pub fn to_vec(&self) -> Result<Vec<T>, WaveError> {
+        (0..self.length())
+            .map(|x| self.get(x).ok_or(WaveError::NotFullyCollapsed))
+            .collect()
+    }
+
+    /// Returns an unbounded sequence of tiles following the rules and the weights of
+    /// the wave, which continues right of the last tile of the wave if it has collapsed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{rules::RuleSet, tile::Tile, wave::{Direction, Wave}};
+    /// # use strum_macros::EnumIter;
+    /// # use rand::thread_rng;
+    /// #[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, Hash, Tile)]
+    /// pub enum Note {
+    ///     C,
+    ///     E,
+    ///     G,
+    ///     Rest,
+    /// }
+    ///
+    /// // notes never repeat, and nothing can follow a rest
+    /// let rules = RuleSet::new()
+    ///     .allow_both_ways(Note::C, Note::E, Direction::Right)
+    ///     .allow_both_ways(Note::E, Note::G, Direction::Right)
+    ///     .allow_both_ways(Note::G, Note::C, Direction::Right)
+    ///     .allow_both_ways(Note::G, Note::Rest, Direction::Right);
+    ///
+    /// let wave = Wave::line(1, rules).unwrap();
+    ///
+    /// let bars = wave.stream(thread_rng()).take(64).collect::<Vec<_>>();
+    ///
+    /// assert_eq!(bars.len(), 64);
+    /// assert!(bars.windows(2).all(|pair| pair[0] != pair[1]));
+    /// assert!(!bars.contains(&Note::Rest)); // it would end the sequence
+    /// ```
+    pub fn stream<R: Rng>(&self, rng: R) -> Stream<T, R> {
+        Stream::new(
+            &self.rules,
+            self.variants.clone(),
+            self.variant_weights(),
+            self.get(self.length() - 1),
+            rng,
+        )
+    }
+}
+
+impl<T: Tile + Hash, P: Topology> Wave<T, P> {
+    /// Returns a new wave laid out following the given topology and the given rules,
+    /// whose tiles can collapse to the given (distinct) variants.
+    ///
+    /// Returns [`WaveError::InvalidVariants`] if there are no variants,
+    /// or if any of them is repeated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{rules::RuleSet, tile::MazeTile, topology::Hex, wave::Wave};
+    /// # use strum::IntoEnumIterator;
+    /// let wave = Wave::with_topology(Hex::new(10, 10), MazeTile::iter().collect(), RuleSet::new())
+    ///     .unwrap();
+    ///
+    /// assert_eq!(wave.width(), 10);
+    /// ```
+    pub fn with_topology<R: Into<Arc<RuleSet<T>>>>(
+        topology: P,
+        variants: Vec<T>,
+        rules: R,
     ) -> Result<Self, WaveError> {
-        if width == 0 || height == 0 {
+        if topology.cells_total() == 0 {
             return Err(WaveError::ZeroDimension);
         }
 
-        let variants_total = T::iter().len();
+        check_variants(&variants)?;
 
         Ok(Self {
-            width,
-            height,
-            tiles: (0..height)
-                .map(|_| (0..width).map(|_| (None, variants_total)).collect())
-                .collect(),
-            variants_total,
-            rules,
+            domains: vec![BitSet::full(variants.len()); topology.cells_total()],
+            allowed: vec![BitSet::full(variants.len()); topology.cells_total()],
+            topology,
+            variants,
+            rules: rules.into(),
+            weights: HashMap::new(),
+            constraints: Constraints::default(),
         })
     }
 
+    /// Returns the topology of the wave.
+    pub fn topology(&self) -> &P {
+        &self.topology
+    }
+
+    /// Returns the variants the tiles of the wave can collapse to.
+    pub fn variants(&self) -> &[T] {
+        &self.variants
+    }
+
+    /// Returns the variant of the tile in the given coordinates,
+    /// or `None` if it hasn't collapsed yet.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the coordinates are out of bounds.
+    pub fn get(&self, coords: P::Coords) -> Option<T> {
+        let cell = self
+            .topology
+            .cell(coords)
+            .expect("coordinates out of bounds");
+
+        self.domains[cell]
+            .single_value()
+            .map(|variant| self.variants[variant])
+    }
+
+    /// Returns the variants the tile in the given coordinates can still collapse to.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the coordinates are out of bounds.
+    pub fn domain(&self, coords: P::Coords) -> Vec<T> {
+        let cell = self
+            .topology
+            .cell(coords)
+            .expect("coordinates out of bounds");
+
+        self.domains[cell]
+            .iter()
+            .map(|variant| self.variants[variant])
+            .collect()
+    }
+
+    /// Pins the tile in the given coordinates to the given variant, and propagates
+    /// it to the rest of the wave.
+    ///
+    /// Returns [`WaveError::UncollapsibleWave`], leaving the wave unchanged, if the
+    /// tile can't collapse to the given variant, or if the rest of the wave can't follow.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the coordinates are out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{rules::RuleSet, tile::MazeTile, wave::{Wave, WaveError}};
+    /// # use rand::thread_rng;
+    /// let mut wave = Wave::<MazeTile>::from_tiles(10, 10).unwrap();
+    ///
+    /// wave.pin((0, 0), MazeTile::TopLeftCorner).unwrap();
+    ///
+    /// assert_eq!(wave.get((0, 0)), Some(MazeTile::TopLeftCorner));
+    /// assert!(!wave.domain((1, 0)).contains(&MazeTile::VerticalLine));
+    ///
+    /// // the corridor of `┏` can't end against `┃`
+    /// assert!(matches!(
+    ///     wave.pin((1, 0), MazeTile::VerticalLine),
+    ///     Err(WaveError::UncollapsibleWave)
+    /// ));
+    ///
+    /// wave.collapse(&mut thread_rng()).unwrap();
+    ///
+    /// assert_eq!(wave.get((0, 0)), Some(MazeTile::TopLeftCorner));
+    /// ```
+    pub fn pin(&mut self, coords: P::Coords, tile: T) -> Result<(), WaveError> {
+        let cell = self
+            .topology
+            .cell(coords)
+            .expect("coordinates out of bounds");
+
+        self.restrict_cells([cell], |_, v| v == tile)
+    }
+
+    /// Restricts the variants each tile can collapse to, following the regions of the
+    /// wave: `region` returns the label of the region of the tile in the given coordinates,
+    /// and `allowed` returns `true` if and only if the given variant can appear in the
+    /// region with the given label; the restrictions are propagated to the rest of the wave.
+    ///
+    /// Returns [`WaveError::UncollapsibleWave`], leaving the wave unchanged,
+    /// if any tile can't collapse to any variant.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{rules::RuleSet, tile::MazeTile, wave::Wave};
+    /// # use rand::thread_rng;
+    /// let mut wave = Wave::<MazeTile>::from_tiles(8, 8).unwrap();
+    ///
+    /// // the playable area is surrounded by an empty ring
+    /// let ring = |(x, y)| x == 0 || y == 0 || x == 7 || y == 7;
+    ///
+    /// wave.restrict_regions(ring, |ring, tile| *ring == (tile == MazeTile::Empty))
+    ///     .unwrap();
+    ///
+    /// assert_eq!(wave.get((7, 3)), Some(MazeTile::Empty));
+    /// assert!(!wave.domain((3, 3)).contains(&MazeTile::Empty));
+    ///
+    /// wave.collapse(&mut thread_rng()).unwrap();
+    ///
+    /// assert_eq!(wave.get((0, 0)), Some(MazeTile::Empty));
+    /// assert_ne!(wave.get((3, 3)), Some(MazeTile::Empty));
+    /// ```
+    pub fn restrict_regions<L, F: Fn(P::Coords) -> L, A: Fn(&L, T) -> bool>(
+        &mut self,
+        region: F,
+        allowed: A,
+    ) -> Result<(), WaveError> {
+        let regions = (0..self.topology.cells_total())
+            .map(|cell| region(self.topology.coords(cell)))
+            .collect::<Vec<_>>();
+
+        self.restrict_cells(0..self.topology.cells_total(), |cell, tile| {
+            allowed(&regions[cell], tile)
+        })
+    }
+
+    /// Restricts the variants the given cells can collapse to, and propagates them to the
+    /// rest of the wave, as long as every cell can still collapse to some variant, where
+    /// `f` returns `true` if and only if the given cell can collapse to the given variant.
+    fn restrict_cells<I: IntoIterator<Item = usize>, F: FnMut(usize, T) -> bool>(
+        &mut self,
+        cells: I,
+        mut f: F,
+    ) -> Result<(), WaveError> {
+        let alloweds = cells
+            .into_iter()
+            .map(|cell| {
+                let mut allowed = BitSet::empty(self.variants.len());
+
+                self.variants
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, tile)| f(cell, **tile))
+                    .for_each(|(variant, _)| allowed.insert(variant));
+
+                (cell, allowed)
+            })
+            .collect::<Vec<_>>();
+
+        let contradiction = alloweds.iter().any(|(cell, allowed)| {
+            let mut domain = self.domains[*cell].clone();
+            let _ = domain.intersect_with(allowed);
+
+            domain.is_empty()
+        });
+
+        if contradiction {
+            return Err(WaveError::UncollapsibleWave);
+        }
+
+        let snapshot = (self.domains.clone(), self.allowed.clone());
+
+        let changed = alloweds
+            .into_iter()
+            .filter_map(|(cell, allowed)| {
+                let _ = self.allowed[cell].intersect_with(&allowed);
+
+                self.domains[cell].intersect_with(&allowed).then_some(cell)
+            })
+            .collect::<Vec<_>>();
+
+        let adjacency = Adjacency::new(&self.rules, &self.variants, &self.topology);
+        let neighbours = Neighbours::new(&self.topology);
+
+        let outcome = self.propagate(&adjacency, &neighbours, changed);
+
+        if outcome.is_err() {
+            (self.domains, self.allowed) = snapshot;
+        }
+
+        outcome
+    }
+
+    /// Uncollapses the tiles in the given region, for which `region` returns `true`,
+    /// so that they can collapse again to any variant they're allowed to (following pins
+    /// and region masks), consistently with the rest of the wave, which is propagated
+    /// to them: collapsing the wave again only collapses them.
+    ///
+    /// Returns [`WaveError::UncollapsibleWave`], leaving the wave unchanged,
+    /// if the rest of the wave leaves any of them without variants.
+    pub fn uncollapse<F: Fn(P::Coords) -> bool>(&mut self, region: F) -> Result<(), WaveError> {
+        let snapshot = self.domains.clone();
+
+        for cell in 0..self.topology.cells_total() {
+            if region(self.topology.coords(cell)) {
+                self.domains[cell] = self.allowed[cell].clone();
+            }
+        }
+
+        let adjacency = Adjacency::new(&self.rules, &self.variants, &self.topology);
+        let neighbours = Neighbours::new(&self.topology);
+
+        let outcome = self.propagate(&adjacency, &neighbours, (0..self.domains.len()).collect());
+
+        if outcome.is_err() {
+            self.domains = snapshot;
+        }
+
+        outcome
+    }
+
     /// Adds a new rule, where `(first_tile, second_tile, direction)` indicates that
     /// `first_tile` can be placed next to `second_tile` in the `direction` direction.
     ///
     /// # Examples
     ///
     /// ```
-    /// # use wfc::{tile::Tile, wave::{Wave, Direction}};
-    /// # use std::{fmt, collections::HashSet};
+    /// # use wfc::{rules::RuleSet, tile::Tile, wave::{Wave, Direction}};
+    /// # use std::fmt;
     /// # use strum_macros::EnumIter;
     /// # #[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, Hash)]
     /// # pub enum SimpleTile {
@@ -90,12 +1064,12 @@ impl<T: Tile + Hash> Wave<T> {
     /// #     Filled,
     /// # }
     /// # impl Tile for SimpleTile {}
-    /// let mut wave = Wave::<SimpleTile>::new(10, 10, HashSet::new()).unwrap();
+    /// let mut wave = Wave::<SimpleTile>::new(10, 10, RuleSet::new()).unwrap();
     ///
     /// wave.add_rule((SimpleTile::Empty, SimpleTile::Filled, Direction::Right)); // now a filled tile can be on the right of an empty tile
     /// ```
     pub fn add_rule(&mut self, rule: (T, T, Direction)) {
-        let _ = self.rules.insert(rule);
+        let _ = Arc::make_mut(&mut self.rules).insert(rule);
     }
 
     /// Removes a new rule, where `(first_tile, second_tile, direction)` indicates that
@@ -104,8 +1078,8 @@ impl<T: Tile + Hash> Wave<T> {
     /// # Examples
     ///
     /// ```
-    /// # use wfc::{tile::Tile, wave::{Wave, Direction}};
-    /// # use std::{fmt, collections::HashSet};
+    /// # use wfc::{rules::RuleSet, tile::Tile, wave::{Wave, Direction}};
+    /// # use std::fmt;
     /// # use strum_macros::EnumIter;
     /// # #[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, Hash)]
     /// # pub enum SimpleTile {
@@ -113,122 +1087,132 @@ impl<T: Tile + Hash> Wave<T> {
     /// #     Filled,
     /// # }
     /// # impl Tile for SimpleTile {}
-    /// let mut wave = Wave::<SimpleTile>::new(10, 10, HashSet::new()).unwrap();
+    /// let mut wave = Wave::<SimpleTile>::new(10, 10, RuleSet::new()).unwrap();
     ///
     /// wave.remove_rule((SimpleTile::Empty, SimpleTile::Filled, Direction::Right)); // now a filled tile can't be on the right of an empty tile anymore
     /// ```
     pub fn remove_rule(&mut self, rule: (T, T, Direction)) {
-        let _ = self.rules.remove(&rule);
+        let _ = Arc::make_mut(&mut self.rules).remove(rule);
     }
 
-    /// Returns the following two arrays:
-    ///   - the first is a `[bool; 4]` array in which the _i_-th element
-    ///   of the array is `true` if and only if the corresponding
-    ///   (following the order defined by [`DIRECTIONS_ORDER`]) neighbour exists;
-    ///   - the second is an `[Option<T>; 4]` array in which the _i_-th element
-    ///   of the array is `None` if the corresponding (following the order defined
-    ///   by [`DIRECTIONS_ORDER`]) neighbour either does not exists or it's `None`
-    ///   because it hasn't collapsed yet.
-    fn neighbours_info(&self, (x, y): (usize, usize)) -> ([bool; 4], [Option<T>; 4]) {
-        let mut availables = [false; 4];
-
-        let mut neighbours = [None; 4];
-
-        if let Some(diff) = y.checked_sub(1) {
-            availables[0] = true;
-
-            neighbours[0] = self.tiles[diff][x].0;
-        }
-
-        if let Some(bottom_row) = self.tiles.get(y + 1) {
-            availables[1] = true;
+    /// Returns the rules followed by the wave.
+    pub fn rules(&self) -> &RuleSet<T> {
+        &self.rules
+    }
 
-            neighbours[1] = bottom_row[x].0;
-        }
+    /// Sets the weight of the given tile variant, which makes it proportionally more
+    /// (or less) likely to be chosen when collapsing a tile; every variant has its
+    /// [`Tile::weight`] by default, and variants with weight `0.0` are never chosen.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{rules::RuleSet, tile::Tile, wave::{Wave, Direction}};
+    /// # use std::fmt;
+    /// # use strum_macros::EnumIter;
+    /// # #[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, Hash)]
+    /// # pub enum SimpleTile {
+    /// #     Empty,
+    /// #     Filled,
+    /// # }
+    /// # impl Tile for SimpleTile {}
+    /// let mut wave = Wave::<SimpleTile>::new(10, 10, RuleSet::new()).unwrap();
+    ///
+    /// wave.set_weight(SimpleTile::Empty, 3.0); // now empty tiles are three times as likely as filled ones
+    ///
+    /// assert_eq!(wave.weight(SimpleTile::Empty), 3.0);
+    /// ```
+    pub fn set_weight(&mut self, tile: T, weight: f64) {
+        let _ = self.weights.insert(tile, weight);
+    }
 
-        if let Some(diff) = x.checked_sub(1) {
-            availables[2] = true;
+    /// Returns the weight of the given tile variant.
+    pub fn weight(&self, tile: T) -> f64 {
+        self.weights
+            .get(&tile)
+            .copied()
+            .unwrap_or_else(|| tile.weight())
+    }
 
-            neighbours[2] = self.tiles[y][diff].0;
-        }
+    /// Returns the weights that have been explicitly set.
+    pub(crate) fn weights(&self) -> &HashMap<T, f64> {
+        &self.weights
+    }
 
-        if let Some(right_tile) = self.tiles.get(y).unwrap().get(x + 1) {
-            availables[3] = true;
+    /// Returns the weight of each variant, indexed by variant.
+    fn variant_weights(&self) -> Vec<f64> {
+        self.variants
+            .iter()
+            .map(|tile| self.weight(*tile))
+            .collect()
+    }
 
-            neighbours[3] = right_tile.0;
-        }
+    /// Adds a global constraint, which the wave propagates together with its rules.
+    ///
+    /// Returns [`WaveError::OutOfBounds`], without adding it, if the constraint
+    /// refers to coordinates out of the bounds of the wave.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wfc::{constraint::Within, tile::MazeTile, wave::{Wave, WaveError}};
+    /// let mut wave = Wave::<MazeTile>::from_tiles(10, 10).unwrap();
+    ///
+    /// assert!(wave.add_constraint(Within::new(MazeTile::CenterCross, (5, 5), 3)).is_ok());
+    ///
+    /// assert!(matches!(
+    ///     wave.add_constraint(Within::new(MazeTile::CenterCross, (10, 5), 3)),
+    ///     Err(WaveError::OutOfBounds)
+    /// ));
+    /// ```
+    pub fn add_constraint<C: Constraint<T, P> + 'static>(
+        &mut self,
+        constraint: C,
+    ) -> Result<(), WaveError> {
+        constraint.check(&self.topology)?;
 
-        (availables, neighbours)
-    }
+        self.constraints.push(Arc::new(constraint));
 
-    /// Returns `true` if and only if the given tile variant
-    /// can be placed next to it's existing collapsed neighbours.
-    fn is_valid_tile_variant(&self, tile_variant: T, neighbours: [Option<T>; 4]) -> bool {
-        neighbours
-            .iter()
-            .zip(DIRECTIONS_ORDER)
-            .all(|(n, d)| n.map_or(true, |v| self.rules.get(&(tile_variant, v, d)).is_some()))
+        Ok(())
     }
 
-    /// Updates the tile in the given coordinates, based on its neighbours.
-    fn update_tile<R: Rng + ?Sized + Clone>(
+    /// Propagates the changes of the given cells to the rest of the wave,
+    /// until both the rules and the constraints don't remove any other variant.
+    fn propagate(
         &mut self,
-        (x, y): (usize, usize),
-        rng: &mut R,
+        adjacency: &Adjacency,
+        neighbours: &Neighbours,
+        mut changed: Vec<usize>,
     ) -> Result<(), WaveError> {
-        let (availables, neighbours) = self.neighbours_info((x, y));
+        while !changed.is_empty() {
+            propagation::propagate(&mut self.domains, adjacency, neighbours, changed)
+                .map_err(|_| WaveError::UncollapsibleWave)?;
 
-        self.tiles[y][x] = (
-            if availables
-                .iter()
-                .zip(neighbours)
-                .all(|(a, n)| !a || n.is_none())
-            {
-                T::iter().choose(rng)
-            } else {
-                let choice = T::iter()
-                    .filter(|tile_variant| self.is_valid_tile_variant(*tile_variant, neighbours))
-                    .choose(rng);
-
-                if choice.is_some() {
-                    choice
-                } else {
-                    return Err(WaveError::UncollapsibleWave);
-                }
-            },
-            0,
-        );
-
-        [
-            (Some(x), y.checked_sub(1)),
-            (Some(x), Some(y + 1)),
-            (x.checked_sub(1), Some(y)),
-            (Some(x + 1), Some(y)),
-        ]
-        .iter()
-        .zip(availables)
-        .zip(neighbours)
-        .filter(|((_, a), n)| *a && n.is_none())
-        .for_each(|(((x, y), _), _)| {
-            let (n_x, n_y) = (x.unwrap(), y.unwrap());
-
-            let (_, neighbours) = self.neighbours_info((n_x, n_y));
-
-            self.tiles[n_y][n_x].1 = T::iter()
-                .filter(|tile_variant| self.is_valid_tile_variant(*tile_variant, neighbours))
-                .count();
-        });
+            let mut domains = Domains::new(&mut self.domains, &self.variants, &self.topology);
+
+            self.constraints.propagate(&mut domains)?;
+
+            changed = domains.changed();
+        }
 
         Ok(())
     }
 
     /// Collapses the wave, using the Wave Function Collapse algorithm.
     ///
+    /// Whenever a tile collapses, the variants that can no longer be placed are removed
+    /// from the rest of the wave: each tile follows the rules against its own neighbours,
+    /// so two variants can be placed next to each other if and only if both `(first_tile,
+    /// second_tile, direction)` and `(second_tile, first_tile, opposite)` are rules.
+    ///
+    /// If a tile is left without variants, the wave can't be fully collapsed,
+    /// and [`WaveError::NotFullyCollapsed`] is returned.
+    ///
     /// # Examples
     ///
     /// ```
-    /// # use wfc::{tile::Tile, wave::{Wave, Direction}};
-    /// # use std::{fmt, collections::HashSet};
+    /// # use wfc::{rules::RuleSet, tile::Tile, wave::{Wave, Direction, WaveError}};
+    /// # use std::fmt;
     /// # use strum_macros::EnumIter;
     /// # use rand::thread_rng;
     /// # #[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, Hash)]
@@ -239,75 +1223,121 @@ impl<T: Tile + Hash> Wave<T> {
     /// # impl Tile for SimpleTile {}
     /// let mut rng = thread_rng();
     ///
-    /// let mut wave = Wave::<SimpleTile>::new(10, 10, HashSet::new()).unwrap();
+    /// let mut wave = Wave::<SimpleTile>::new(10, 10, RuleSet::new()).unwrap();
     ///
     /// wave.add_rule((SimpleTile::Empty, SimpleTile::Filled, Direction::Right));
     ///
     /// let outcome = wave.collapse(&mut rng);
+    ///
+    /// // filled tiles are never on the left of empty ones, without the inverse rule
+    /// assert!(matches!(outcome, Err(WaveError::NotFullyCollapsed)));
     /// ```
-    pub fn collapse<R: Rng + ?Sized + Clone>(&mut self, rng: &mut R) -> Result<(), WaveError> {
-        let mut collapsed = 0;
+    pub fn collapse<R: Rng + ?Sized>(&mut self, rng: &mut R) -> Result<(), WaveError> {
+        let adjacency = Adjacency::new(&self.rules, &self.variants, &self.topology);
+        let neighbours = Neighbours::new(&self.topology);
 
-        let total_tiles = self.width * self.height;
+        let weights = self.variant_weights();
 
-        while collapsed < total_tiles {
-            let lowest_entropy = self
-                .tiles
-                .iter()
-                .map(|row| {
-                    row.iter()
-                        .map(|tile| tile.1)
-                        .filter(|entropy| *entropy != 0)
-                        .min()
-                        .unwrap_or(self.variants_total)
-                })
-                .min()
-                .unwrap();
-
-            let tile_coords = self
-                .tiles
-                .iter()
-                .enumerate()
-                .flat_map(|(y, row)| {
-                    row.iter()
-                        .enumerate()
-                        .filter(|(_, tile)| tile.1 == lowest_entropy)
-                        .map(move |(x, _)| (x, y))
-                })
-                .choose(rng);
-
-            if tile_coords.is_none() || self.update_tile(tile_coords.unwrap(), rng).is_err() {
-                return Err(WaveError::NotFullyCollapsed);
-            }
+        if self.domains.iter().any(BitSet::is_empty) {
+            return Err(WaveError::NotFullyCollapsed);
+        }
 
-            collapsed += 1;
+        self.propagate(&adjacency, &neighbours, (0..self.domains.len()).collect())
+            .map_err(not_fully_collapsed)?;
+
+        while let Some(cell) = propagation::lowest_entropy(&self.domains, rng) {
+            let variant = propagation::choose_variant(&self.domains[cell], &weights, rng)
+                .ok_or(WaveError::NotFullyCollapsed)?;
+
+            self.domains[cell] = BitSet::single(self.variants.len(), variant);
+
+            self.propagate(&adjacency, &neighbours, vec![cell])
+                .map_err(not_fully_collapsed)?;
         }
 
         Ok(())
     }
 }
 
-impl<T: Tile + Hash + fmt::Display> fmt::Display for Wave<T> {
+/// Renders the wave row by row, where the holes of its grid are rendered as spaces, and the
+/// cells of staggered grids are separated by a space, with odd rows shifted right by one.
+impl<T: Tile + Hash + fmt::Display, P: Grid> fmt::Display for Wave<T, P> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.tiles.iter().try_for_each(|row| {
-            row.iter().try_for_each(|tile| {
-                if let Some(v) = tile.0 {
-                    write!(f, "{}", v)
-                } else {
-                    write!(f, "X")
-                }
-            })?;
-            writeln!(f)
+        fmt_grid(f, &self.topology, |coords| {
+            self.get(coords).map(|tile| tile::glyph(&tile))
         })
     }
 }
 
+impl<T: Tile + Hash + fmt::Display> fmt::Display for Wave<T, Line> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_grid(f, &Square::new(self.length(), 1), |(x, _)| {
+            self.get(x).map(|tile| tile::glyph(&tile))
+        })
+    }
+}
+
+/// Renders the wave one horizontal layer at a time, from the bottom one, with
+/// `x` growing to the right and `z` growing downwards, separating layers with an empty line.
+impl<T: Tile + Hash + fmt::Display> fmt::Display for Wave<T, Voxel> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let layer = Square::new(self.width(), self.depth());
+
+        (0..self.height()).try_for_each(|y| {
+            if y != 0 {
+                writeln!(f)?;
+            }
+
+            fmt_grid(f, &layer, |(x, z)| {
+                self.get((x, y, z)).map(|tile| tile::glyph(&tile))
+            })
+        })
+    }
+}
+
+/// Writes the given grid row by row, where collapsed tiles are rendered through the glyphs
+/// returned by `glyph`, and tiles that haven't collapsed yet (for which it returns `None`)
+/// are rendered as `X`.
+pub(crate) fn fmt_grid<W, G, D, F>(f: &mut W, grid: &G, glyph: F) -> fmt::Result
+where
+    W: fmt::Write,
+    G: Grid,
+    D: fmt::Display,
+    F: Fn((usize, usize)) -> Option<D>,
+{
+    (0..grid.height()).try_for_each(|y| {
+        if grid.is_staggered() && y % 2 == 1 {
+            write!(f, " ")?;
+        }
+
+        (0..grid.width()).try_for_each(|x| {
+            if grid.is_staggered() && x != 0 {
+                write!(f, " ")?;
+            }
+
+            if grid.cell((x, y)).is_none() {
+                return write!(f, " ");
+            }
+
+            match glyph((x, y)) {
+                Some(glyph) => write!(f, "{}", glyph),
+                None => write!(f, "X"),
+            }
+        })?;
+        writeln!(f)
+    })
+}
+
 /// Represents possible errors for the [`Wave`] structure.
 #[derive(Debug)]
 pub enum WaveError {
     ZeroDimension,
     NotFullyCollapsed,
     UncollapsibleWave,
+    UnsatisfiedCount,
+    InvalidVariants,
+    OutOfBounds,
+    UnsupportedTopology,
 }
 
 impl std::fmt::Display for WaveError {
@@ -316,8 +1346,37 @@ impl std::fmt::Display for WaveError {
             Self::ZeroDimension => writeln!(f, "Dimensions can't be 0."),
             Self::NotFullyCollapsed => writeln!(f, "The wave has not fully collapsed."),
             Self::UncollapsibleWave => writeln!(f, "Tha wave can't be collapsed any further."),
+            Self::UnsatisfiedCount => {
+                writeln!(f, "The number of tiles of a variant is out of bounds.")
+            }
+            Self::InvalidVariants => {
+                writeln!(f, "The variants must be distinct, and at least one.")
+            }
+            Self::OutOfBounds => writeln!(f, "The coordinates are out of bounds."),
+            Self::UnsupportedTopology => {
+                writeln!(f, "The constraint can't apply to the topology of the wave.")
+            }
         }
     }
 }
 
 impl std::error::Error for WaveError {}
+
+/// Returns [`WaveError::InvalidVariants`] if there are no given variants,
+/// or if any of them is repeated.
+pub(crate) fn check_variants<T: Tile>(variants: &[T]) -> Result<(), WaveError> {
+    if variants.is_empty() || variants.iter().collect::<HashSet<_>>().len() != variants.len() {
+        return Err(WaveError::InvalidVariants);
+    }
+
+    Ok(())
+}
+
+/// Returns [`WaveError::NotFullyCollapsed`] in place of [`WaveError::UncollapsibleWave`],
+/// which is how a tile left without variants is reported while collapsing a wave.
+fn not_fully_collapsed(error: WaveError) -> WaveError {
+    match error {
+        WaveError::UncollapsibleWave => WaveError::NotFullyCollapsed,
+        error => error,
+    }
+}
@@ -0,0 +1,344 @@
+//! Derive macro for the `Tile` trait of the `wfc` crate.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    parse_macro_input, Attribute, Data, DeriveInput, Error, Fields, Ident, Lit, LitStr, Variant,
+};
+
+/// Derives the `Tile` trait.
+///
+/// On enums, each variant can be annotated with a `#[tile(...)]` attribute, whose
+/// keys override the corresponding methods of the trait for that variant:
+///   - `glyph = "..."`, the glyph of the variant;
+///   - `name = "..."`, the name of the variant inside rule files;
+///   - `weight = ...`, the weight of the variant;
+///   - `sockets = "..."`, the sockets of the variant, with one label for each
+///     direction of the topology the rules are built for, which is checked by
+///     `RuleSet::from_sockets_in`;
+///   - `symmetry(horizontal = ..., vertical = ...)`, the variants obtained by
+///     mirroring the variant, which default to the variant itself.
+///
+/// The enum itself can be annotated with `#[tile(display)]`, to implement `Display`
+/// by writing the glyphs of the variants, which must all have one.
+///
+/// Only enums and their variants can be annotated, and only unit
+/// variants can have weights, sockets and symmetries.
+///
+/// # Examples
+///
+/// ```
+/// use wfc::{format::RuleFile, tile::Tile, wave::Direction};
+/// use strum_macros::EnumIter;
+///
+/// #[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, Hash, Tile)]
+/// #[tile(display)]
+/// pub enum Pipe {
+///     #[tile(glyph = "━", weight = 2.0, sockets = "0011")]
+///     Horizontal,
+///
+///     #[tile(glyph = "┃", weight = 2.0, sockets = "1100")]
+///     Vertical,
+///
+///     #[tile(glyph = "┏", sockets = "0101", symmetry(horizontal = TopRight))]
+///     TopLeft,
+///
+///     #[tile(glyph = "┓", name = "corner", sockets = "0110", symmetry(horizontal = TopLeft))]
+///     TopRight,
+/// }
+///
+/// assert_eq!(Pipe::TopLeft.to_string(), "┏");
+/// assert_eq!(Pipe::TopRight.name(), Some("corner"));
+/// assert_eq!(Pipe::Horizontal.weight(), 2.0);
+/// assert_eq!(Pipe::TopLeft.weight(), 1.0);
+/// assert_eq!(Pipe::TopLeft.symmetry().horizontal, Pipe::TopRight);
+/// assert_eq!(Pipe::TopLeft.symmetry().vertical, Pipe::TopLeft);
+///
+/// let file = RuleFile::<Pipe>::from_tiles();
+///
+/// assert_eq!(file.weights[&Pipe::Horizontal], 2.0);
+/// assert_eq!(file.symmetries[&Pipe::TopLeft].horizontal, Pipe::TopRight);
+/// assert!(file.all_rules().contains((Pipe::TopLeft, Pipe::TopRight, Direction::Right)));
+/// ```
+#[proc_macro_derive(Tile, attributes(tile))]
+pub fn derive_tile(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    expand(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Represents the attributes of a variant.
+#[derive(Default)]
+struct Attributes {
+    glyph: Option<LitStr>,
+    name: Option<LitStr>,
+    weight: Option<f64>,
+    sockets: Option<LitStr>,
+    horizontal: Option<Ident>,
+    vertical: Option<Ident>,
+}
+
+impl Attributes {
+    /// Parses the `#[tile(...)]` attributes of the given variant.
+    fn of(variant: &Variant) -> Result<Self, Error> {
+        let mut attributes = Self::default();
+
+        for attribute in variant.attrs.iter().filter(|a| is_tile(a)) {
+            attribute.parse_nested_meta(|meta| {
+                if meta.path.is_ident("glyph") {
+                    attributes.glyph = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("name") {
+                    attributes.name = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("weight") {
+                    let weight = match meta.value()?.parse()? {
+                        Lit::Float(weight) => weight.base10_parse()?,
+                        Lit::Int(weight) => weight.base10_parse()?,
+                        lit => return Err(Error::new_spanned(lit, "expected a number")),
+                    };
+
+                    attributes.weight = Some(weight);
+                } else if meta.path.is_ident("sockets") {
+                    attributes.sockets = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("symmetry") {
+                    meta.parse_nested_meta(|meta| {
+                        if meta.path.is_ident("horizontal") {
+                            attributes.horizontal = Some(meta.value()?.parse()?);
+                        } else if meta.path.is_ident("vertical") {
+                            attributes.vertical = Some(meta.value()?.parse()?);
+                        } else {
+                            return Err(meta.error("expected `horizontal` or `vertical`"));
+                        }
+
+                        Ok(())
+                    })?;
+                } else {
+                    return Err(
+                        meta.error("expected `glyph`, `name`, `weight`, `sockets` or `symmetry`")
+                    );
+                }
+
+                Ok(())
+            })?;
+        }
+
+        if !matches!(variant.fields, Fields::Unit)
+            && (attributes.weight.is_some()
+                || attributes.sockets.is_some()
+                || attributes.horizontal.is_some()
+                || attributes.vertical.is_some())
+        {
+            return Err(Error::new_spanned(
+                variant,
+                "only unit variants can have weights, sockets and symmetries",
+            ));
+        }
+
+        Ok(attributes)
+    }
+}
+
+/// Returns the implementations derived for the given type.
+fn expand(input: &DeriveInput) -> Result<TokenStream2, Error> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let fields = match &input.data {
+        Data::Struct(data) => data.fields.iter().collect(),
+        Data::Union(data) => data.fields.named.iter().collect(),
+        Data::Enum(data) => data
+            .variants
+            .iter()
+            .flat_map(|variant| &variant.fields)
+            .collect::<Vec<_>>(),
+    };
+
+    let own = match &input.data {
+        Data::Enum(_) => &[][..],
+        _ => &input.attrs[..],
+    };
+
+    if let Some(attribute) = own
+        .iter()
+        .chain(fields.into_iter().flat_map(|field| &field.attrs))
+        .find(|a| is_tile(a))
+    {
+        return Err(Error::new_spanned(
+            attribute,
+            "`#[tile(...)]` can only be used on enums and their variants",
+        ));
+    }
+
+    let Data::Enum(data) = &input.data else {
+        return Ok(quote! {
+            impl #impl_generics ::wfc::tile::Tile for #name #ty_generics #where_clause {}
+        });
+    };
+
+    let mut display = false;
+
+    for attribute in input.attrs.iter().filter(|a| is_tile(a)) {
+        attribute.parse_nested_meta(|meta| {
+            if meta.path.is_ident("display") {
+                display = true;
+            } else {
+                return Err(meta.error("expected `display`"));
+            }
+
+            Ok(())
+        })?;
+    }
+
+    let variants = data
+        .variants
+        .iter()
+        .map(|variant| Ok((&variant.ident, Attributes::of(variant)?)))
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    let glyph = method(
+        &variants,
+        quote! { fn glyph(&self) -> ::std::option::Option<&'static str> },
+        |_, attributes| {
+            let glyph = attributes.glyph.as_ref()?;
+
+            Some(quote! { ::std::option::Option::Some(#glyph) })
+        },
+        quote! { ::std::option::Option::None },
+    );
+
+    let tile_name = method(
+        &variants,
+        quote! { fn name(&self) -> ::std::option::Option<&'static str> },
+        |_, attributes| {
+            let name = attributes.name.as_ref()?;
+
+            Some(quote! { ::std::option::Option::Some(#name) })
+        },
+        quote! { ::std::option::Option::None },
+    );
+
+    let weight = method(
+        &variants,
+        quote! { fn weight(&self) -> f64 },
+        |_, attributes| {
+            let weight = attributes.weight?;
+
+            Some(quote! { #weight })
+        },
+        quote! { 1.0 },
+    );
+
+    let sockets = method(
+        &variants,
+        quote! { fn sockets(&self) -> ::std::option::Option<&'static str> },
+        |_, attributes| {
+            let sockets = attributes.sockets.as_ref()?;
+
+            Some(quote! { ::std::option::Option::Some(#sockets) })
+        },
+        quote! { ::std::option::Option::None },
+    );
+
+    let symmetry = method(
+        &variants,
+        quote! { fn symmetry(&self) -> ::wfc::tile::Symmetry<Self> },
+        |variant, attributes| {
+            if attributes.horizontal.is_none() && attributes.vertical.is_none() {
+                return None;
+            }
+
+            let horizontal = attributes.horizontal.as_ref().unwrap_or(variant);
+            let vertical = attributes.vertical.as_ref().unwrap_or(variant);
+
+            Some(quote! {
+                ::wfc::tile::Symmetry {
+                    horizontal: Self::#horizontal,
+                    vertical: Self::#vertical,
+                }
+            })
+        },
+        quote! {
+            ::wfc::tile::Symmetry {
+                horizontal: *self,
+                vertical: *self,
+            }
+        },
+    );
+
+    let display = if display {
+        let arms = variants
+            .iter()
+            .map(|(variant, attributes)| match &attributes.glyph {
+                Some(glyph) => Ok(quote! { Self::#variant { .. } => f.write_str(#glyph), }),
+                None => Err(Error::new_spanned(
+                    variant,
+                    "every variant must have a glyph, to implement `Display`",
+                )),
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        quote! {
+            impl #impl_generics ::std::fmt::Display for #name #ty_generics #where_clause {
+                fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                    match *self {
+                        #(#arms)*
+                    }
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::wfc::tile::Tile for #name #ty_generics #where_clause {
+            #glyph
+            #tile_name
+            #weight
+            #sockets
+            #symmetry
+        }
+
+        #display
+    })
+}
+
+/// Returns `true` if and only if the given attribute is a `#[tile(...)]` attribute.
+fn is_tile(attribute: &Attribute) -> bool {
+    attribute.path().is_ident("tile")
+}
+
+/// Returns the given method of the trait, matching on the variants to return the values
+/// given by `value`, or `default` for the variants without one; if no variant has
+/// a value, the method isn't overridden at all.
+fn method<F: Fn(&Ident, &Attributes) -> Option<TokenStream2>>(
+    variants: &[(&Ident, Attributes)],
+    signature: TokenStream2,
+    value: F,
+    default: TokenStream2,
+) -> TokenStream2 {
+    let values = variants
+        .iter()
+        .map(|(variant, attributes)| value(variant, attributes))
+        .collect::<Vec<_>>();
+
+    if values.iter().all(Option::is_none) {
+        return quote! {};
+    }
+
+    let arms = variants.iter().zip(values).map(|((variant, _), value)| {
+        let value = value.unwrap_or_else(|| default.clone());
+
+        quote! { Self::#variant { .. } => #value, }
+    });
+
+    quote! {
+        #signature {
+            match *self {
+                #(#arms)*
+            }
+        }
+    }
+}